@@ -0,0 +1,420 @@
+//! Derive macros for the `ToTerm` and `FromTerm` traits, and the `term_match!` macro, of the
+//! [eetf](https://docs.rs/eetf) crate.
+//!
+//! Use them through the `derive` feature of `eetf` rather than directly.
+//!
+//! # Representations
+//!
+//! | Rust                          | Erlang (default)          | Attributes                                  |
+//! |-------------------------------|---------------------------|---------------------------------------------|
+//! | `struct S { a, b }`           | `#{a => A, b => B}`       | `record` / `record = "r"`: `{s, A, B}`, `tuple`: `{A, B}` |
+//! | `struct S(a, b)`              | `{A, B}`                  | `record` / `record = "r"`: `{s, A, B}`        |
+//! | `struct S(a)`                 | `A`                       | `tuple`: `{A}`, `record`: `{s, A}`            |
+//! | `struct S;`                   | `s`                       | `record`: `{s}`                              |
+//! | `E::V`                        | `v`                       |                                             |
+//! | `E::V(a, b)`                  | `{v, A, B}`               |                                             |
+//! | `E::V { a, b }`               | `{v, A, B}`               | `map`: `{v, #{a => A, b => B}}`               |
+//!
+//! Names are converted to snake case (`UserAccount` becomes `user_account`), and
+//! `#[eetf(rename = "...")]` on a struct, variant or field overrides them.
+//! `#[eetf(atom)]` on an enum checks that all the variants are unit variants, and
+//! `#[eetf(map)]` on an enum applies to all of its struct variants.
+extern crate proc_macro;
+
+mod term_match;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Generics, Ident, LitStr,
+    Result,
+};
+
+#[proc_macro_derive(ToTerm, attributes(eetf))]
+pub fn derive_to_term(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_term(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromTerm, attributes(eetf))]
+pub fn derive_from_term(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_term(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Matches a term against Erlang-like patterns, trying the clauses in order.
+///
+/// `term_match!(term, Pattern => expr, ...)` evaluates to `Ok(expr)` of the first matching
+/// clause, or `Err` with the deepest `Unmatch` of all the clauses. Each pattern compiles
+/// to the patterns of `eetf::pattern`:
+///
+/// - `{P, ...}`, `[P, ...]`, `[H, ... | T]` and `#{K := P, ...}` match tuples, lists, lists
+///   (including improper ones) starting with the given elements, and maps with (at least)
+///   the given keys. The tail `T` is a variable bound to `eetf::pattern::Tail`, `_`, or a
+///   list pattern such as `[H | [A, B]]` (which is `[H, A, B]`).
+///   Map keys are atoms, literals or parenthesized expressions (but not variables).
+/// - Lowercase identifiers match atoms, and literals (`1`, `-1`, `"abc"`, `<<"abc">>`) match
+///   equal terms, as in the `eetf!` macro.
+/// - Variables (`Name`, `_Name`) bind the matched `&Term`, and `_` matches anything.
+/// - `Name @ expr` binds the output of the pattern `expr` (e.g. `Age @ U8`), and `(expr)`
+///   matches with the pattern `expr` without binding.
+///
+/// See the `eetf` crate, which re-exports this macro, for examples.
+#[proc_macro]
+pub fn term_match(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as term_match::TermMatch);
+    term_match::expand(&input).into()
+}
+
+#[derive(Default)]
+struct Attrs {
+    record: Option<Option<String>>,
+    tuple: bool,
+    map: bool,
+    atom: bool,
+    rename: Option<String>,
+}
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Attrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("eetf")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("record") {
+                    result.record = Some(if meta.input.peek(syn::Token![=]) {
+                        Some(meta.value()?.parse::<LitStr>()?.value())
+                    } else {
+                        None
+                    });
+                } else if meta.path.is_ident("rename") {
+                    result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("tuple") {
+                    result.tuple = true;
+                } else if meta.path.is_ident("map") {
+                    result.map = true;
+                } else if meta.path.is_ident("atom") {
+                    result.atom = true;
+                } else {
+                    return Err(meta.error("unknown eetf attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(result)
+    }
+
+    fn name(&self, ident: &Ident) -> String {
+        self.rename
+            .clone()
+            .unwrap_or_else(|| snake_case(&ident.to_string()))
+    }
+}
+
+/// How a struct or an enum variant is represented.
+enum Repr {
+    /// `#{field => Value, ...}`, optionally wrapped in a `{Tag, Map}` tuple.
+    Map { tag: Option<String> },
+    /// `{Value, ...}`, optionally preceded by a tag.
+    Tuple { tag: Option<String> },
+    /// The representation of the single field.
+    Transparent,
+    /// An atom.
+    Atom(String),
+}
+
+fn struct_repr(ident: &Ident, attrs: &Attrs, fields: &Fields) -> Result<Repr> {
+    let record = attrs
+        .record
+        .as_ref()
+        .map(|name| name.clone().unwrap_or_else(|| attrs.name(ident)));
+    Ok(match *fields {
+        Fields::Named(_) if record.is_some() => Repr::Tuple { tag: record },
+        Fields::Named(_) if attrs.tuple => Repr::Tuple { tag: None },
+        Fields::Named(_) => Repr::Map { tag: None },
+        _ if attrs.map => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`map` requires named fields",
+            ))
+        }
+        Fields::Unnamed(ref x) if x.unnamed.len() == 1 && record.is_none() && !attrs.tuple => {
+            Repr::Transparent
+        }
+        Fields::Unnamed(_) => Repr::Tuple { tag: record },
+        Fields::Unit if record.is_some() => Repr::Tuple { tag: record },
+        Fields::Unit => Repr::Atom(attrs.name(ident)),
+    })
+}
+
+fn variant_repr(ident: &Ident, attrs: &Attrs, enum_attrs: &Attrs, fields: &Fields) -> Repr {
+    let tag = attrs.name(ident);
+    match *fields {
+        Fields::Named(_) if attrs.map || enum_attrs.map => Repr::Map { tag: Some(tag) },
+        Fields::Named(_) | Fields::Unnamed(_) => Repr::Tuple { tag: Some(tag) },
+        Fields::Unit => Repr::Atom(tag),
+    }
+}
+
+/// Returns the key (for named fields) and the binding of each field.
+fn field_bindings(fields: &Fields) -> Result<Vec<(Option<String>, Ident)>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let attrs = Attrs::parse(&field.attrs)?;
+            Ok(match field.ident {
+                Some(ref ident) => (
+                    Some(attrs.rename.unwrap_or_else(|| ident.unraw().to_string())),
+                    format_ident!("__{}", ident.unraw()),
+                ),
+                None => (None, format_ident!("__{}", i)),
+            })
+        })
+        .collect()
+}
+
+/// Returns a pattern which binds the fields of `path` to the identifiers of `bindings`.
+fn destructure(
+    path: TokenStream2,
+    fields: &Fields,
+    bindings: &[(Option<String>, Ident)],
+) -> TokenStream2 {
+    let vars = bindings.iter().map(|(_, var)| var);
+    match *fields {
+        Fields::Named(ref x) => {
+            let names = x.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#names: #vars),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#vars),*)),
+        Fields::Unit => path,
+    }
+}
+
+fn add_bound(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+fn atom(name: &str) -> TokenStream2 {
+    quote!(::eetf::Term::from(::eetf::Atom::from(#name)))
+}
+
+fn to_term_body(repr: &Repr, bindings: &[(Option<String>, Ident)]) -> TokenStream2 {
+    let values = bindings
+        .iter()
+        .map(|(_, var)| quote!(::eetf::convert::ToTerm::to_term(#var)));
+    match *repr {
+        Repr::Map { ref tag } => {
+            let keys = bindings.iter().map(|(key, _)| atom(key.as_ref().unwrap()));
+            let map = quote! {
+                ::eetf::Term::from(::eetf::Map::from(vec![#((#keys, #values)),*]))
+            };
+            match *tag {
+                Some(ref tag) => {
+                    let tag = atom(tag);
+                    quote!(::eetf::Term::from(::eetf::Tuple::from(vec![#tag, #map])))
+                }
+                None => map,
+            }
+        }
+        Repr::Tuple { ref tag } => {
+            let tag = tag.iter().map(|tag| atom(tag));
+            quote!(::eetf::Term::from(::eetf::Tuple::from(
+                vec![#(#tag,)* #(#values),*]
+            )))
+        }
+        Repr::Transparent => quote!(#(#values)*),
+        Repr::Atom(ref name) => atom(name),
+    }
+}
+
+fn expand_to_term(input: &DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let attrs = Attrs::parse(&input.attrs)?;
+    let arms = match input.data {
+        Data::Struct(ref data) => {
+            let repr = struct_repr(ident, &attrs, &data.fields)?;
+            let bindings = field_bindings(&data.fields)?;
+            let pattern = destructure(quote!(Self), &data.fields, &bindings);
+            let body = to_term_body(&repr, &bindings);
+            vec![quote!(#pattern => #body)]
+        }
+        Data::Enum(ref data) => {
+            check_enum(&attrs, data)?;
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let variant_attrs = Attrs::parse(&variant.attrs)?;
+                let repr = variant_repr(&variant.ident, &variant_attrs, &attrs, &variant.fields);
+                let bindings = field_bindings(&variant.fields)?;
+                let name = &variant.ident;
+                let pattern = destructure(quote!(Self::#name), &variant.fields, &bindings);
+                let body = to_term_body(&repr, &bindings);
+                arms.push(quote!(#pattern => #body));
+            }
+            arms
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(ident, "unions are not supported"));
+        }
+    };
+    let generics = add_bound(&input.generics, quote!(::eetf::convert::ToTerm));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::eetf::convert::ToTerm for #ident #ty_generics #where_clause {
+            fn to_term(&self) -> ::eetf::Term {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+fn check_enum(attrs: &Attrs, data: &syn::DataEnum) -> Result<()> {
+    if attrs.atom {
+        for variant in &data.variants {
+            if !variant.fields.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "`atom` enums can only have unit variants",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns an expression which converts `term` into `path` (a struct or a variant).
+fn from_term_body(
+    repr: &Repr,
+    path: TokenStream2,
+    fields: &Fields,
+    bindings: &[(Option<String>, Ident)],
+    expected: &str,
+) -> TokenStream2 {
+    let vars = bindings.iter().map(|(_, var)| var);
+    let construct = destructure(path, fields, bindings);
+    match *repr {
+        Repr::Map { ref tag } => {
+            let keys = bindings.iter().map(|(key, _)| key.as_ref().unwrap());
+            let map = match *tag {
+                Some(ref tag) => quote! {{
+                    let mut tuple = ::eetf::convert::TupleFields::new(term, Some(#tag), 1, #expected)?;
+                    tuple.take()?
+                }},
+                None => quote!(term),
+            };
+            quote! {{
+                let mut fields = ::eetf::convert::MapFields::new(#map, #expected)?;
+                #(let #vars = fields.take(#keys)?;)*
+                Ok(#construct)
+            }}
+        }
+        Repr::Tuple { ref tag } => {
+            let tag = match *tag {
+                Some(ref tag) => quote!(Some(#tag)),
+                None => quote!(None),
+            };
+            let len = bindings.len();
+            quote! {{
+                let mut fields = ::eetf::convert::TupleFields::new(term, #tag, #len, #expected)?;
+                #(let #vars = fields.take()?;)*
+                Ok(#construct)
+            }}
+        }
+        Repr::Transparent => quote! {{
+            #(let #vars = ::eetf::convert::FromTerm::from_term(term)?;)*
+            Ok(#construct)
+        }},
+        Repr::Atom(ref name) => quote! {
+            match term {
+                ::eetf::Term::Atom(ref x) if x.name == #name => Ok(#construct),
+                _ => ::eetf::convert::unexpected_type(term, #expected),
+            }
+        },
+    }
+}
+
+fn expand_from_term(input: &DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let expected = ident.to_string();
+    let attrs = Attrs::parse(&input.attrs)?;
+    let body = match input.data {
+        Data::Struct(ref data) => {
+            let repr = struct_repr(ident, &attrs, &data.fields)?;
+            let bindings = field_bindings(&data.fields)?;
+            from_term_body(&repr, quote!(Self), &data.fields, &bindings, &expected)
+        }
+        Data::Enum(ref data) => {
+            check_enum(&attrs, data)?;
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let variant_attrs = Attrs::parse(&variant.attrs)?;
+                let repr = variant_repr(&variant.ident, &variant_attrs, &attrs, &variant.fields);
+                let bindings = field_bindings(&variant.fields)?;
+                let name = &variant.ident;
+                let (tag, arity) = match repr {
+                    Repr::Map { ref tag } => (tag.clone().unwrap(), 1),
+                    Repr::Tuple { ref tag } => (tag.clone().unwrap(), bindings.len()),
+                    Repr::Atom(ref tag) => (tag.clone(), 0),
+                    Repr::Transparent => unreachable!(),
+                };
+                let body = from_term_body(
+                    &repr,
+                    quote!(Self::#name),
+                    &variant.fields,
+                    &bindings,
+                    &expected,
+                );
+                arms.push(quote!((#tag, #arity) => #body));
+            }
+            quote! {
+                let (tag, arity) = match ::eetf::convert::variant(&term) {
+                    Some(variant) => variant,
+                    None => return ::eetf::convert::unexpected_type(term, #expected),
+                };
+                match (tag.as_str(), arity) {
+                    #(#arms,)*
+                    _ => ::eetf::convert::unexpected_type(term, #expected),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(ident, "unions are not supported"));
+        }
+    };
+    let generics = add_bound(&input.generics, quote!(::eetf::convert::FromTerm));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::eetf::convert::FromTerm for #ident #ty_generics #where_clause {
+            fn from_term(term: ::eetf::Term) -> ::std::result::Result<Self, ::eetf::DecodeError> {
+                #body
+            }
+        }
+    })
+}
+
+/// Converts a Rust type or variant name into snake case, e.g. `HttpRequest` into `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
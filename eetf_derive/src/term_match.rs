@@ -0,0 +1,278 @@
+//! The `term_match!` macro.
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, bracketed, parenthesized, Expr, Ident, Lit, Result, Token};
+
+pub struct TermMatch {
+    input: Expr,
+    arms: Vec<Arm>,
+}
+impl Parse for TermMatch {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let mut arms = Vec::new();
+        while !input.is_empty() {
+            let pattern = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            let body: Expr = input.parse()?;
+            let is_block = matches!(body, Expr::Block(_));
+            arms.push(Arm { pattern, body });
+            if input.is_empty() {
+                break;
+            }
+            if input.peek(Token![,]) || !is_block {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        if arms.is_empty() {
+            return Err(input.error("expected at least one clause"));
+        }
+        Ok(TermMatch { input: expr, arms })
+    }
+}
+
+struct Arm {
+    pattern: Pat,
+    body: Expr,
+}
+
+enum Pat {
+    Atom(String),
+    Bind(Ident),
+    Wild,
+    Tuple(Vec<Pat>),
+    List(Vec<Pat>),
+    Cons(Vec<Pat>, Option<Ident>),
+    Map(Vec<(TokenStream2, Pat)>),
+    Literal(TokenStream2),
+    As(Ident, Expr),
+    Expr(Expr),
+}
+impl Parse for Pat {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            let elements = content.parse_terminated(Pat::parse, Token![,])?;
+            Ok(Pat::Tuple(elements.into_iter().collect()))
+        } else if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            parse_list(&content)
+        } else if input.peek(Token![#]) {
+            input.parse::<Token![#]>()?;
+            let content;
+            braced!(content in input);
+            let entries = content.parse_terminated(parse_map_entry, Token![,])?;
+            Ok(Pat::Map(entries.into_iter().collect()))
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Ok(Pat::Expr(content.parse()?))
+        } else if input.peek(Token![_]) {
+            input.parse::<Token![_]>()?;
+            Ok(Pat::Wild)
+        } else if input.peek(Ident::peek_any) {
+            let ident = Ident::parse_any(input)?;
+            let name = ident.to_string();
+            if !is_variable(&name) {
+                Ok(Pat::Atom(name))
+            } else if input.peek(Token![@]) {
+                input.parse::<Token![@]>()?;
+                Ok(Pat::As(ident, input.parse()?))
+            } else {
+                Ok(Pat::Bind(ident))
+            }
+        } else if input.peek(Token![<<]) || input.peek(Token![-]) || input.peek(Lit) {
+            Ok(Pat::Literal(parse_literal(input)?))
+        } else {
+            Err(input.error("expected a term pattern"))
+        }
+    }
+}
+
+/// Parses the contents of `[...]`: `P, ...` or `P, ... | T`.
+///
+/// A list pattern as the tail is merged into the heads, e.g. `[H | [A, B]]` is `[H, A, B]`.
+fn parse_list(input: ParseStream) -> Result<Pat> {
+    let mut elements = Vec::new();
+    while !input.is_empty() {
+        elements.push(input.parse()?);
+        if input.peek(Token![|]) {
+            input.parse::<Token![|]>()?;
+            let span = input.span();
+            return match input.parse()? {
+                Pat::List(tail) => {
+                    elements.extend(tail);
+                    Ok(Pat::List(elements))
+                }
+                Pat::Cons(heads, tail) => {
+                    elements.extend(heads);
+                    Ok(Pat::Cons(elements, tail))
+                }
+                Pat::Bind(ident) => Ok(Pat::Cons(elements, Some(ident))),
+                Pat::Wild => Ok(Pat::Cons(elements, None)),
+                _ => Err(syn::Error::new(
+                    span,
+                    "the tail of a list must be a variable, `_` or a list pattern",
+                )),
+            };
+        }
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    Ok(Pat::List(elements))
+}
+
+/// Parses `Key := Pattern`, where the key is an atom, a literal or a parenthesized expression.
+fn parse_map_entry(input: ParseStream) -> Result<(TokenStream2, Pat)> {
+    let key = if input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let expr: Expr = content.parse()?;
+        quote!(::eetf::Term::from(#expr))
+    } else if input.peek(Ident::peek_any) {
+        let ident = Ident::parse_any(input)?;
+        let name = ident.to_string();
+        if is_variable(&name) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "a map key cannot be a variable (use `(Atom::from(\"...\"))` for such an atom)",
+            ));
+        }
+        quote!(#name)
+    } else {
+        let literal = parse_literal(input)?;
+        quote!(::eetf::eetf!(#literal))
+    };
+    input.parse::<Token![:]>()?;
+    input.parse::<Token![=]>()?;
+    Ok((key, input.parse()?))
+}
+
+/// Parses a literal, a negative number or a binary, as tokens for the `eetf!` macro.
+fn parse_literal(input: ParseStream) -> Result<TokenStream2> {
+    if input.peek(Token![<<]) {
+        input.parse::<Token![<<]>()?;
+        let mut body = TokenStream2::new();
+        while !input.peek(Token![>>]) {
+            if input.is_empty() {
+                return Err(input.error("expected `>>`"));
+            }
+            body.extend(Some(input.parse::<proc_macro2::TokenTree>()?));
+        }
+        input.parse::<Token![>>]>()?;
+        Ok(quote!(<< #body >>))
+    } else if input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        let lit: Lit = input.parse()?;
+        Ok(quote!(- #lit))
+    } else {
+        let lit: Lit = input.parse()?;
+        Ok(quote!(#lit))
+    }
+}
+
+fn is_variable(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_uppercase() || c == '_')
+}
+
+/// Returns the pattern and the Rust pattern which destructures its output.
+fn expand_pattern(pattern: &Pat) -> (TokenStream2, TokenStream2) {
+    match *pattern {
+        Pat::Atom(ref name) => (quote!(#name), quote!(_)),
+        Pat::Bind(ref ident) => (
+            quote!(::eetf::pattern::any::<::eetf::Term>()),
+            quote!(#ident),
+        ),
+        Pat::Wild => (quote!(::eetf::pattern::any::<::eetf::Term>()), quote!(_)),
+        Pat::Literal(ref tokens) => (
+            quote!(::eetf::pattern::Eq(::eetf::eetf!(#tokens))),
+            quote!(_),
+        ),
+        Pat::As(ref ident, ref expr) => (quote!(#expr), quote!(#ident)),
+        Pat::Expr(ref expr) => (quote!(#expr), quote!(_)),
+        Pat::Tuple(ref elements) => {
+            let (patterns, outputs): (Vec<_>, Vec<_>) = elements.iter().map(expand_pattern).unzip();
+            match elements.len() {
+                0 => (quote!(()), quote!(())),
+                1 => (quote!((#(#patterns,)*)), quote!(#(#outputs)*)),
+                // Rust tuples are patterns up to 12 elements (the limit of `Debug` in `std`)
+                2..=12 => (quote!((#(#patterns,)*)), quote!((#(#outputs,)*))),
+                _ => (
+                    quote!(::eetf::pattern::TupleOf((#(#patterns,)*))),
+                    quote!((#(#outputs,)*)),
+                ),
+            }
+        }
+        Pat::List(ref elements) => {
+            let (patterns, outputs): (Vec<_>, Vec<_>) = elements.iter().map(expand_pattern).unzip();
+            match elements.len() {
+                0 => (quote!(::eetf::pattern::Nil), quote!(_)),
+                1 => (
+                    quote!(::eetf::pattern::FixList((#(#patterns,)*))),
+                    quote!(#(#outputs)*),
+                ),
+                _ => (
+                    quote!(::eetf::pattern::FixList((#(#patterns,)*))),
+                    quote!((#(#outputs,)*)),
+                ),
+            }
+        }
+        Pat::Cons(ref heads, ref tail) => {
+            let (patterns, outputs): (Vec<_>, Vec<_>) = heads.iter().map(expand_pattern).unzip();
+            let tail = match *tail {
+                Some(ref ident) => quote!(#ident),
+                None => quote!(_),
+            };
+            (
+                quote!(::eetf::pattern::Prefix((#(#patterns,)*))),
+                quote!(((#(#outputs,)*), #tail)),
+            )
+        }
+        Pat::Map(ref entries) => {
+            if entries.is_empty() {
+                return (quote!(::eetf::pattern::any::<::eetf::Map>()), quote!(_));
+            }
+            let keys = entries.iter().map(|(k, _)| k);
+            let (patterns, outputs): (Vec<_>, Vec<_>) =
+                entries.iter().map(|(_, v)| expand_pattern(v)).unzip();
+            (
+                quote!(::eetf::pattern::MapWith((#((#keys, #patterns),)*))),
+                quote!((#(#outputs,)*)),
+            )
+        }
+    }
+}
+
+pub fn expand(input: &TermMatch) -> TokenStream2 {
+    let term = &input.input;
+    let mut clauses = quote!(::std::result::Result::Err(__unmatch));
+    for (i, arm) in input.arms.iter().enumerate().rev() {
+        let (pattern, output) = expand_pattern(&arm.pattern);
+        let body = &arm.body;
+        let on_error = if i == 0 {
+            quote!(let __unmatch = __e;)
+        } else {
+            quote!(let __unmatch = __e.max_depth(__unmatch);)
+        };
+        clauses = quote! {
+            match ::eetf::pattern::Pattern::try_match(&(#pattern), __input) {
+                #[allow(non_snake_case)]
+                ::std::result::Result::Ok(#output) => ::std::result::Result::Ok(#body),
+                ::std::result::Result::Err(__e) => {
+                    #on_error
+                    #clauses
+                }
+            }
+        };
+    }
+    quote! {{
+        let __input: &::eetf::Term = &(#term);
+        #clauses
+    }}
+}
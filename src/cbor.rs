@@ -0,0 +1,245 @@
+//! Conversion between terms and [CBOR](https://cbor.io) values of
+//! [ciborium](https://docs.rs/ciborium).
+//!
+//! This module is available if the `cbor` feature is enabled.
+//!
+//! # Mapping
+//!
+//! | CBOR                         | Erlang                                          |
+//! |------------------------------|-------------------------------------------------|
+//! | null                         | `null`                                          |
+//! | boolean                      | `true` / `false`                                |
+//! | integer, bignum (tag 2, 3)   | integer                                         |
+//! | float                        | float                                           |
+//! | text, bytes                  | binary (UTF-8 binaries are written as text)     |
+//! | array                        | list                                            |
+//! | map                          | map                                             |
+//! | tag `ATOM_TAG`               | other atoms (a text of the name)                |
+//! | tag `TUPLE_TAG`              | tuple (an array of the elements)                |
+//! | tag `IMPROPER_LIST_TAG`      | improper list (an array of the elements and the tail) |
+//! | tag `BITSTRING_TAG`          | bitstring (an array of the number of bits in the last byte and the bytes) |
+//! | tag `TERM_TAG`               | pids, ports, references and funs (bytes of ETF) |
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::{cbor, Term};
+//!
+//! let term: Term = "{ok,[1,<<\"a\">>],<nonode@nohost.1.2>}".parse().unwrap();
+//! let bytes = cbor::to_vec(&term).unwrap();
+//! assert_eq!(cbor::from_slice(&bytes).unwrap(), term);
+//! ```
+use super::*;
+use ciborium::value::{Integer, Value};
+use num::bigint::Sign;
+use std::error;
+
+/// The tag of atoms other than `null`, `true` and `false`.
+pub const ATOM_TAG: u64 = 0x4554_4601;
+/// The tag of tuples.
+pub const TUPLE_TAG: u64 = 0x4554_4602;
+/// The tag of improper lists.
+pub const IMPROPER_LIST_TAG: u64 = 0x4554_4603;
+/// The tag of bitstrings.
+pub const BITSTRING_TAG: u64 = 0x4554_4604;
+/// The tag of terms encoded in the External Term Format.
+pub const TERM_TAG: u64 = 0x4554_4605;
+
+const BIGPOS_TAG: u64 = 2;
+const BIGNEG_TAG: u64 = 3;
+
+/// Errors which can occur when converting between terms and CBOR
+#[derive(Debug)]
+pub enum Error {
+    Encode(Box<EncodeError>),
+    Decode(Box<DecodeError>),
+    Invalid { message: String },
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(ref x) => x.fmt(f),
+            Error::Decode(ref x) => x.fmt(f),
+            Error::Invalid { ref message } => write!(f, "Invalid CBOR data: {}", message),
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Encode(_) => "Cannot encode the term",
+            Error::Decode(_) => "Cannot decode the term",
+            Error::Invalid { .. } => "Invalid CBOR data",
+        }
+    }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref x) => Some(&**x),
+            Error::Decode(ref x) => Some(&**x),
+            Error::Invalid { .. } => None,
+        }
+    }
+}
+impl From<EncodeError> for Error {
+    fn from(err: EncodeError) -> Self {
+        Error::Encode(Box::new(err))
+    }
+}
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::Decode(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Converts a term into a CBOR value.
+pub fn to_value(term: &Term) -> Result<Value> {
+    Ok(match *term {
+        Term::Atom(ref x) => match x.name.as_str() {
+            "null" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            name => tagged(ATOM_TAG, Value::Text(name.to_owned())),
+        },
+        Term::FixInteger(ref x) => Value::Integer(Integer::from(x.value)),
+        Term::BigInteger(ref x) => bigint_value(&x.value),
+        Term::Float(ref x) => Value::Float(x.value),
+        Term::Binary(ref x) => match str::from_utf8(&x.bytes) {
+            Ok(s) => Value::Text(s.to_owned()),
+            Err(_) => Value::Bytes(x.bytes.clone()),
+        },
+        Term::BitBinary(ref x) => tagged(
+            BITSTRING_TAG,
+            Value::Array(vec![
+                Value::Integer(Integer::from(x.tail_bits_size)),
+                Value::Bytes(x.bytes.clone()),
+            ]),
+        ),
+        Term::List(ref x) => Value::Array(to_values(&x.elements)?),
+        Term::ImproperList(ref x) => {
+            let mut elements = to_values(&x.elements)?;
+            elements.push(to_value(&x.last)?);
+            tagged(IMPROPER_LIST_TAG, Value::Array(elements))
+        }
+        Term::Tuple(ref x) => tagged(TUPLE_TAG, Value::Array(to_values(&x.elements)?)),
+        Term::Map(ref x) => Value::Map(
+            x.entries
+                .iter()
+                .map(|(k, v)| Ok((to_value(k)?, to_value(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+        Term::Pid(_)
+        | Term::Port(_)
+        | Term::Reference(_)
+        | Term::ExternalFun(_)
+        | Term::InternalFun(_) => {
+            let mut buf = Vec::new();
+            term.encode(&mut buf)?;
+            tagged(TERM_TAG, Value::Bytes(buf))
+        }
+    })
+}
+
+/// Converts a CBOR value into a term.
+pub fn from_value(value: &Value) -> Result<Term> {
+    Ok(match *value {
+        Value::Null => Term::from(Atom::from("null")),
+        Value::Bool(x) => Term::from(Atom::from(if x { "true" } else { "false" })),
+        Value::Integer(x) => integer_term(BigInt::from(i128::from(x))),
+        Value::Float(x) => Term::from(Float::try_from(x).map_err(|_| invalid("non-finite float"))?),
+        Value::Text(ref x) => Term::from(Binary::from(x.as_bytes())),
+        Value::Bytes(ref x) => Term::from(Binary::from(x.as_slice())),
+        Value::Array(ref x) => Term::from(List::from(from_values(x)?)),
+        Value::Map(ref x) => Term::from(Map::from(
+            x.iter()
+                .map(|(k, v)| Ok((from_value(k)?, from_value(v)?)))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        Value::Tag(tag, ref content) => from_tagged(tag, content)?,
+        _ => return Err(invalid("unsupported value")),
+    })
+}
+
+/// Converts a term into a CBOR value and encodes it into a byte vector.
+pub fn to_vec(term: &Term) -> Result<Vec<u8>> {
+    let value = to_value(term)?;
+    let mut buf = Vec::new();
+    ciborium::into_writer(&value, &mut buf).map_err(|e| invalid(&e.to_string()))?;
+    Ok(buf)
+}
+
+/// Decodes a CBOR value from `bytes` and converts it into a term.
+pub fn from_slice(bytes: &[u8]) -> Result<Term> {
+    let value: Value = ciborium::from_reader(bytes).map_err(|e| invalid(&e.to_string()))?;
+    from_value(&value)
+}
+
+fn from_tagged(tag: u64, content: &Value) -> Result<Term> {
+    Ok(match (tag, content) {
+        (ATOM_TAG, Value::Text(name)) => Term::from(Atom::from(name.as_str())),
+        (TUPLE_TAG, Value::Array(elements)) => Term::from(Tuple::from(from_values(elements)?)),
+        (IMPROPER_LIST_TAG, Value::Array(elements)) => {
+            let mut elements = from_values(elements)?;
+            match elements.pop() {
+                Some(last) if !elements.is_empty() => {
+                    Term::from(ImproperList::from((elements, last)))
+                }
+                _ => return Err(invalid("improper list has less than two elements")),
+            }
+        }
+        (BITSTRING_TAG, Value::Array(x)) => match x.as_slice() {
+            [Value::Integer(bits), Value::Bytes(bytes)] => match u8::try_from(*bits) {
+                Ok(bits) if (1..=8).contains(&bits) && !bytes.is_empty() => {
+                    Term::from(BitBinary::from((bytes.clone(), bits)))
+                }
+                _ => return Err(invalid("malformed bitstring")),
+            },
+            _ => return Err(invalid("malformed bitstring")),
+        },
+        (TERM_TAG, Value::Bytes(bytes)) => Term::decode(io::Cursor::new(bytes))?,
+        (BIGPOS_TAG, Value::Bytes(bytes)) => integer_term(BigInt::from_bytes_be(Sign::Plus, bytes)),
+        (BIGNEG_TAG, Value::Bytes(bytes)) => {
+            integer_term(-BigInt::from_bytes_be(Sign::Plus, bytes) - BigInt::from(1))
+        }
+        _ => return Err(invalid(&format!("unexpected content of tag {}", tag))),
+    })
+}
+
+fn to_values(terms: &[Term]) -> Result<Vec<Value>> {
+    terms.iter().map(to_value).collect()
+}
+
+fn from_values(values: &[Value]) -> Result<Vec<Term>> {
+    values.iter().map(from_value).collect()
+}
+
+fn tagged(tag: u64, content: Value) -> Value {
+    Value::Tag(tag, Box::new(content))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Invalid {
+        message: message.to_owned(),
+    }
+}
+
+fn bigint_value(n: &BigInt) -> Value {
+    if let Some(x) = n.to_i128().and_then(|x| Integer::try_from(x).ok()) {
+        return Value::Integer(x);
+    }
+    if n.sign() == Sign::Minus {
+        let (_, bytes) = (-n - BigInt::from(1)).to_bytes_be();
+        tagged(BIGNEG_TAG, Value::Bytes(bytes))
+    } else {
+        let (_, bytes) = n.to_bytes_be();
+        tagged(BIGPOS_TAG, Value::Bytes(bytes))
+    }
+}
+
+fn integer_term(n: BigInt) -> Term {
+    match n.to_i32() {
+        Some(x) => Term::from(FixInteger::from(x)),
+        None => Term::from(BigInteger { value: n }),
+    }
+}
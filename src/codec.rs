@@ -1,6 +1,5 @@
 use super::*;
 use crate::convert::TryAsRef;
-use crate::convert::TryInto;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
@@ -8,6 +7,7 @@ use libflate::zlib;
 use num::bigint::BigInt;
 use std;
 use std::convert::From;
+use std::convert::TryInto;
 use std::error;
 use std::fmt;
 use std::io;
@@ -715,8 +715,8 @@ impl<W: io::Write> Encoder<W> {
 }
 
 mod aux {
-    use crate::convert::TryInto;
     use num::bigint::Sign;
+    use std::convert::TryInto;
     use std::io;
     use std::ops::Range;
     use std::str;
@@ -1,5 +1,8 @@
 use super::*;
 use num;
+use num::bigint::ToBigInt;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
 
 pub trait TryAsRef<T> {
     fn try_as_ref(&self) -> Option<&T>;
@@ -39,51 +42,34 @@ impl_term_try_as_ref!(ImproperList);
 impl_term_try_as_ref!(Tuple);
 impl_term_try_as_ref!(Map);
 
-pub trait TryInto<T> {
-    fn try_into(self) -> Result<T, Self>
-    where
-        Self: Sized;
-}
-
-impl<T> TryInto<T> for T {
-    fn try_into(self) -> Result<T, Self>
-    where
-        Self: Sized,
-    {
-        Ok(self)
-    }
-}
-
-macro_rules! impl_term_try_into {
+macro_rules! impl_term_try_from {
     ($to:ident) => {
-        impl TryInto<$to> for Term {
-            fn try_into(self) -> Result<$to, Self>
-            where
-                Self: Sized,
-            {
-                match self {
+        impl TryFrom<Term> for $to {
+            type Error = Term;
+            fn try_from(term: Term) -> Result<Self, Term> {
+                match term {
                     Term::$to(x) => Ok(x),
-                    _ => Err(self),
+                    _ => Err(term),
                 }
             }
         }
     };
 }
-impl_term_try_into!(Atom);
-impl_term_try_into!(FixInteger);
-impl_term_try_into!(BigInteger);
-impl_term_try_into!(Float);
-impl_term_try_into!(Pid);
-impl_term_try_into!(Port);
-impl_term_try_into!(Reference);
-impl_term_try_into!(ExternalFun);
-impl_term_try_into!(InternalFun);
-impl_term_try_into!(Binary);
-impl_term_try_into!(BitBinary);
-impl_term_try_into!(List);
-impl_term_try_into!(ImproperList);
-impl_term_try_into!(Tuple);
-impl_term_try_into!(Map);
+impl_term_try_from!(Atom);
+impl_term_try_from!(FixInteger);
+impl_term_try_from!(BigInteger);
+impl_term_try_from!(Float);
+impl_term_try_from!(Pid);
+impl_term_try_from!(Port);
+impl_term_try_from!(Reference);
+impl_term_try_from!(ExternalFun);
+impl_term_try_from!(InternalFun);
+impl_term_try_from!(Binary);
+impl_term_try_from!(BitBinary);
+impl_term_try_from!(List);
+impl_term_try_from!(ImproperList);
+impl_term_try_from!(Tuple);
+impl_term_try_from!(Map);
 
 pub trait AsOption {
     fn as_option(&self) -> Option<&Self>;
@@ -116,6 +102,12 @@ impl num::traits::ToPrimitive for BigInteger {
     fn to_u64(&self) -> Option<u64> {
         self.value.to_u64()
     }
+    fn to_i128(&self) -> Option<i128> {
+        self.value.to_i128()
+    }
+    fn to_u128(&self) -> Option<u128> {
+        self.value.to_u128()
+    }
     fn to_f64(&self) -> Option<f64> {
         self.value.to_f64()
     }
@@ -146,6 +138,20 @@ impl num::traits::ToPrimitive for Term {
             _ => None,
         }
     }
+    fn to_i128(&self) -> Option<i128> {
+        match *self {
+            Term::FixInteger(ref x) => x.to_i128(),
+            Term::BigInteger(ref x) => x.to_i128(),
+            _ => None,
+        }
+    }
+    fn to_u128(&self) -> Option<u128> {
+        match *self {
+            Term::FixInteger(ref x) => x.to_u128(),
+            Term::BigInteger(ref x) => x.to_u128(),
+            _ => None,
+        }
+    }
     fn to_f64(&self) -> Option<f64> {
         match *self {
             Term::FixInteger(ref x) => x.to_f64(),
@@ -195,3 +201,534 @@ impl num::bigint::ToBigUint for Term {
         }
     }
 }
+
+/// Conversion of a Rust value into a term.
+///
+/// It can be derived with `#[derive(ToTerm)]` if the `derive` feature is enabled.
+pub trait ToTerm {
+    fn to_term(&self) -> Term;
+}
+
+/// Conversion of a term into a Rust value.
+///
+/// It can be derived with `#[derive(FromTerm)]` if the `derive` feature is enabled.
+pub trait FromTerm: Sized {
+    fn from_term(term: Term) -> Result<Self, DecodeError>;
+
+    /// Returns the value used when a map key or proplist entry is missing
+    /// (`None` means that the key is required).
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+#[doc(hidden)]
+pub fn unexpected_type<T>(value: Term, expected: &str) -> Result<T, DecodeError> {
+    Err(DecodeError::UnexpectedType {
+        value,
+        expected: expected.to_owned(),
+    })
+}
+
+impl ToTerm for Term {
+    fn to_term(&self) -> Term {
+        self.clone()
+    }
+}
+impl FromTerm for Term {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        Ok(term)
+    }
+}
+
+macro_rules! impl_term_struct_conversions {
+    ($t:ident) => {
+        impl ToTerm for $t {
+            fn to_term(&self) -> Term {
+                Term::from(self.clone())
+            }
+        }
+        impl FromTerm for $t {
+            fn from_term(term: Term) -> Result<Self, DecodeError> {
+                $t::try_from(term).or_else(|term| unexpected_type(term, stringify!($t)))
+            }
+        }
+    };
+}
+impl_term_struct_conversions!(Atom);
+impl_term_struct_conversions!(FixInteger);
+impl_term_struct_conversions!(BigInteger);
+impl_term_struct_conversions!(Float);
+impl_term_struct_conversions!(Pid);
+impl_term_struct_conversions!(Port);
+impl_term_struct_conversions!(Reference);
+impl_term_struct_conversions!(ExternalFun);
+impl_term_struct_conversions!(InternalFun);
+impl_term_struct_conversions!(Binary);
+impl_term_struct_conversions!(BitBinary);
+impl_term_struct_conversions!(List);
+impl_term_struct_conversions!(ImproperList);
+impl_term_struct_conversions!(Tuple);
+impl_term_struct_conversions!(Map);
+
+macro_rules! impl_integer_conversions {
+    ($t:ident, $to:ident, $big:ty) => {
+        impl ToTerm for $t {
+            fn to_term(&self) -> Term {
+                match i32::try_from(*self) {
+                    Ok(x) => Term::from(FixInteger::from(x)),
+                    Err(_) => Term::from(BigInteger::from(*self as $big)),
+                }
+            }
+        }
+        impl FromTerm for $t {
+            fn from_term(term: Term) -> Result<Self, DecodeError> {
+                // `ToPrimitive::to_u64` wraps negative fix integers around.
+                match term.to_bigint().and_then(|n| n.$to()) {
+                    Some(x) => Ok(x),
+                    None => unexpected_type(term, stringify!($t)),
+                }
+            }
+        }
+        impl From<$t> for Term {
+            fn from(x: $t) -> Self {
+                x.to_term()
+            }
+        }
+        impl TryFrom<Term> for $t {
+            type Error = DecodeError;
+            fn try_from(term: Term) -> Result<Self, DecodeError> {
+                Self::from_term(term)
+            }
+        }
+    };
+}
+impl_integer_conversions!(i8, to_i8, i64);
+impl_integer_conversions!(i16, to_i16, i64);
+impl_integer_conversions!(i32, to_i32, i64);
+impl_integer_conversions!(i64, to_i64, i64);
+impl_integer_conversions!(u8, to_u8, u64);
+impl_integer_conversions!(u16, to_u16, u64);
+impl_integer_conversions!(u32, to_u32, u64);
+impl_integer_conversions!(u64, to_u64, u64);
+impl_integer_conversions!(i128, to_i128, i128);
+impl_integer_conversions!(u128, to_u128, u128);
+
+// There is no `ToTerm` for `f64`, as Erlang has no NaN or infinite floats
+// (use `Term::try_from` or `Float` instead).
+impl FromTerm for f64 {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term.to_f64() {
+            Some(x) => Ok(x),
+            None => unexpected_type(term, "f64"),
+        }
+    }
+}
+impl TryFrom<f64> for Term {
+    type Error = DecodeError;
+    fn try_from(x: f64) -> Result<Self, DecodeError> {
+        Float::try_from(x).map(Term::from)
+    }
+}
+impl TryFrom<Term> for f64 {
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+impl ToTerm for bool {
+    fn to_term(&self) -> Term {
+        Term::from(Atom::from(if *self { "true" } else { "false" }))
+    }
+}
+impl FromTerm for bool {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term {
+            Term::Atom(ref x) if x.name == "true" => Ok(true),
+            Term::Atom(ref x) if x.name == "false" => Ok(false),
+            _ => unexpected_type(term, "bool"),
+        }
+    }
+}
+impl From<bool> for Term {
+    fn from(x: bool) -> Self {
+        x.to_term()
+    }
+}
+impl TryFrom<Term> for bool {
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+/// Strings are converted into binaries, and can be converted back from binaries or charlists.
+impl ToTerm for String {
+    fn to_term(&self) -> Term {
+        Term::from(Binary::from(self.as_bytes()))
+    }
+}
+impl FromTerm for String {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        let s = match term {
+            Term::Binary(ref x) => str::from_utf8(&x.bytes).ok().map(ToOwned::to_owned),
+            Term::List(ref x) => x
+                .elements
+                .iter()
+                .map(|e| e.to_u32().and_then(std::char::from_u32))
+                .collect(),
+            _ => None,
+        };
+        match s {
+            Some(s) => Ok(s),
+            None => unexpected_type(term, "String"),
+        }
+    }
+}
+impl From<String> for Term {
+    fn from(x: String) -> Self {
+        Term::from(Binary::from(x.into_bytes()))
+    }
+}
+impl TryFrom<Term> for String {
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+impl ToTerm for str {
+    fn to_term(&self) -> Term {
+        Term::from(Binary::from(self.as_bytes()))
+    }
+}
+impl<'a> From<&'a str> for Term {
+    fn from(x: &'a str) -> Self {
+        x.to_term()
+    }
+}
+
+impl<T: ToTerm + ?Sized> ToTerm for &T {
+    fn to_term(&self) -> Term {
+        (**self).to_term()
+    }
+}
+
+impl<T: ToTerm> ToTerm for Vec<T> {
+    fn to_term(&self) -> Term {
+        Term::from(List::from(
+            self.iter().map(ToTerm::to_term).collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<T: FromTerm> FromTerm for Vec<T> {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term {
+            Term::List(x) => x.elements.into_iter().map(T::from_term).collect(),
+            _ => unexpected_type(term, "List"),
+        }
+    }
+}
+impl<T: Into<Term>> From<Vec<T>> for Term {
+    fn from(x: Vec<T>) -> Self {
+        Term::from(List::from(
+            x.into_iter().map(Into::into).collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<T: FromTerm> TryFrom<Term> for Vec<T> {
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+impl<T: ToTerm> ToTerm for [T] {
+    fn to_term(&self) -> Term {
+        Term::from(List::from(
+            self.iter().map(ToTerm::to_term).collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<'a, T: ToTerm> From<&'a [T]> for Term {
+    fn from(x: &'a [T]) -> Self {
+        x.to_term()
+    }
+}
+
+impl<K, V, S> ToTerm for HashMap<K, V, S>
+where
+    K: ToTerm,
+    V: ToTerm,
+{
+    fn to_term(&self) -> Term {
+        Term::from(Map::from(
+            self.iter()
+                .map(|(k, v)| (k.to_term(), v.to_term()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<K, V, S> FromTerm for HashMap<K, V, S>
+where
+    K: FromTerm + Eq + Hash,
+    V: FromTerm,
+    S: BuildHasher + Default,
+{
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term {
+            Term::Map(x) => x
+                .entries
+                .into_iter()
+                .map(|(k, v)| Ok((K::from_term(k)?, V::from_term(v)?)))
+                .collect(),
+            _ => unexpected_type(term, "Map"),
+        }
+    }
+}
+impl<K, V, S> From<HashMap<K, V, S>> for Term
+where
+    K: Into<Term>,
+    V: Into<Term>,
+{
+    fn from(x: HashMap<K, V, S>) -> Self {
+        Term::from(Map::from(
+            x.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<K, V, S> TryFrom<Term> for HashMap<K, V, S>
+where
+    K: FromTerm + Eq + Hash,
+    V: FromTerm,
+    S: BuildHasher + Default,
+{
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+impl<K, V> ToTerm for BTreeMap<K, V>
+where
+    K: ToTerm,
+    V: ToTerm,
+{
+    fn to_term(&self) -> Term {
+        Term::from(Map::from(
+            self.iter()
+                .map(|(k, v)| (k.to_term(), v.to_term()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<K, V> FromTerm for BTreeMap<K, V>
+where
+    K: FromTerm + Ord,
+    V: FromTerm,
+{
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term {
+            Term::Map(x) => x
+                .entries
+                .into_iter()
+                .map(|(k, v)| Ok((K::from_term(k)?, V::from_term(v)?)))
+                .collect(),
+            _ => unexpected_type(term, "Map"),
+        }
+    }
+}
+impl<K, V> From<BTreeMap<K, V>> for Term
+where
+    K: Into<Term>,
+    V: Into<Term>,
+{
+    fn from(x: BTreeMap<K, V>) -> Self {
+        Term::from(Map::from(
+            x.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+impl<K, V> TryFrom<Term> for BTreeMap<K, V>
+where
+    K: FromTerm + Ord,
+    V: FromTerm,
+{
+    type Error = DecodeError;
+    fn try_from(term: Term) -> Result<Self, DecodeError> {
+        Self::from_term(term)
+    }
+}
+
+macro_rules! impl_tuple_conversions {
+    ($len:expr; $($t:ident $i:tt),+) => {
+        impl<$($t: ToTerm),+> ToTerm for ($($t,)+) {
+            fn to_term(&self) -> Term {
+                Term::from(Tuple::from(vec![$(self.$i.to_term()),+]))
+            }
+        }
+        impl<$($t: FromTerm),+> FromTerm for ($($t,)+) {
+            fn from_term(term: Term) -> Result<Self, DecodeError> {
+                let mut fields = TupleFields::new(term, None, $len, "Tuple")?;
+                Ok(($(fields.take::<$t>()?,)+))
+            }
+        }
+        impl<$($t: Into<Term>),+> From<($($t,)+)> for Term {
+            fn from(x: ($($t,)+)) -> Self {
+                Term::from(Tuple::from(vec![$(x.$i.into()),+]))
+            }
+        }
+        impl<$($t: FromTerm),+> TryFrom<Term> for ($($t,)+) {
+            type Error = DecodeError;
+            fn try_from(term: Term) -> Result<Self, DecodeError> {
+                Self::from_term(term)
+            }
+        }
+    };
+}
+impl_tuple_conversions!(1; A 0);
+impl_tuple_conversions!(2; A 0, B 1);
+impl_tuple_conversions!(3; A 0, B 1, C 2);
+impl_tuple_conversions!(4; A 0, B 1, C 2, D 3);
+impl_tuple_conversions!(5; A 0, B 1, C 2, D 3, E 4);
+impl_tuple_conversions!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple_conversions!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple_conversions!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_tuple_conversions!(9; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_tuple_conversions!(10; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_tuple_conversions!(11; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_tuple_conversions!(12; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// `None` is converted into the `undefined` atom.
+impl<T: ToTerm> ToTerm for Option<T> {
+    fn to_term(&self) -> Term {
+        match *self {
+            Some(ref x) => x.to_term(),
+            None => Term::from(Atom::from("undefined")),
+        }
+    }
+}
+impl<T: FromTerm> FromTerm for Option<T> {
+    fn from_term(term: Term) -> Result<Self, DecodeError> {
+        match term {
+            Term::Atom(ref x) if x.name == "undefined" => Ok(None),
+            _ => T::from_term(term).map(Some),
+        }
+    }
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+impl<T: Into<Term>> From<Option<T>> for Term {
+    fn from(x: Option<T>) -> Self {
+        match x {
+            Some(x) => x.into(),
+            None => Term::from(Atom::from("undefined")),
+        }
+    }
+}
+
+// A generic `TryFrom<Term> for Option<T>` would overlap with the blanket
+// `TryFrom<Term> for Option<Term>` of the standard library.
+macro_rules! impl_option_try_from {
+    ($($t:ty),*) => {
+        $(impl TryFrom<Term> for Option<$t> {
+            type Error = DecodeError;
+            fn try_from(term: Term) -> Result<Self, DecodeError> {
+                Self::from_term(term)
+            }
+        })*
+    };
+}
+impl_option_try_from!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f64, bool, String);
+impl_option_try_from!(Atom, FixInteger, BigInteger, Float, Pid, Port, Reference);
+impl_option_try_from!(ExternalFun, InternalFun, Binary, BitBinary);
+impl_option_try_from!(List, ImproperList, Tuple, Map);
+
+/// Support code for `#[derive(FromTerm)]`: the fields of a map.
+#[doc(hidden)]
+pub struct MapFields {
+    map: Map,
+    expected: &'static str,
+}
+impl MapFields {
+    pub fn new(term: Term, expected: &'static str) -> Result<Self, DecodeError> {
+        match term {
+            Term::Map(map) => Ok(MapFields { map, expected }),
+            _ => unexpected_type(term, expected),
+        }
+    }
+
+    pub fn take<T: FromTerm>(&mut self, key: &str) -> Result<T, DecodeError> {
+        let position = self.map.entries.iter().position(|(k, _)| match *k {
+            Term::Atom(ref k) => k.name == key,
+            _ => false,
+        });
+        match position {
+            Some(i) => T::from_term(self.map.entries.swap_remove(i).1),
+            None => T::from_missing().ok_or_else(|| DecodeError::UnexpectedType {
+                value: Term::from(self.map.clone()),
+                expected: format!("{} (missing key {})", self.expected, key),
+            }),
+        }
+    }
+}
+
+/// Support code for `#[derive(FromTerm)]`: the elements of a (tagged) tuple.
+#[doc(hidden)]
+pub struct TupleFields {
+    elements: std::vec::IntoIter<Term>,
+}
+impl TupleFields {
+    pub fn new(
+        term: Term,
+        tag: Option<&str>,
+        len: usize,
+        expected: &'static str,
+    ) -> Result<Self, DecodeError> {
+        let offset = if tag.is_some() { 1 } else { 0 };
+        let matched = match term {
+            Term::Tuple(ref x) => {
+                x.elements.len() == len + offset
+                    && tag.map_or(true, |tag| match x.elements[0] {
+                        Term::Atom(ref a) => a.name == tag,
+                        _ => false,
+                    })
+            }
+            _ => false,
+        };
+        match term {
+            Term::Tuple(x) if matched => {
+                let mut elements = x.elements.into_iter();
+                if tag.is_some() {
+                    elements.next();
+                }
+                Ok(TupleFields { elements })
+            }
+            _ => unexpected_type(term, expected),
+        }
+    }
+
+    pub fn take<T: FromTerm>(&mut self) -> Result<T, DecodeError> {
+        T::from_term(self.elements.next().expect("too few elements"))
+    }
+}
+
+/// Support code for `#[derive(FromTerm)]`: returns the tag and the number of the other
+/// elements of an atom or a tagged tuple.
+#[doc(hidden)]
+pub fn variant(term: &Term) -> Option<(String, usize)> {
+    match *term {
+        Term::Atom(ref x) => Some((x.name.clone(), 0)),
+        Term::Tuple(ref x) => match x.elements.first() {
+            Some(Term::Atom(tag)) => Some((tag.name.clone(), x.elements.len() - 1)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
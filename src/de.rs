@@ -0,0 +1,580 @@
+//! Deserialization of terms into Rust values with [serde](https://serde.rs).
+//!
+//! This module is available if the `serde` feature is enabled.
+//!
+//! # Mapping
+//!
+//! The mapping is the reverse of the one in the `ser` module, with a few extra
+//! shapes accepted where Erlang code commonly uses them:
+//!
+//! - Strings are read from binaries (UTF-8), charlists and atoms.
+//! - `None` is read from `undefined` and `nil`.
+//! - Sequences are read from lists, tuples and binaries (as bytes).
+//! - Maps are read from maps and from proplists (`[{Key, Value}]`).
+//! - Structs are read from maps with atom (or binary) keys, from proplists, where a
+//!   bare atom `Key` stands for `{Key, true}`, and from records `{Name, Field1, ...}`.
+//!   The `__struct__` key of Elixir structs is ignored unless it is a field.
+//! - Enums are read from atoms (unit variants) and from tagged tuples:
+//!   `{Tag, V}` for newtype variants, `{Tag, A, B}` for tuple variants, and
+//!   `{Tag, #{...}}` or `{Tag, A, B}` for struct variants.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::{de, Term};
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! #[serde(rename_all = "snake_case")]
+//! enum Reply {
+//!     Ok(User),
+//!     Error(String),
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct User {
+//!     name: String,
+//!     admin: bool,
+//!     email: Option<String>,
+//! }
+//!
+//! let term: Term = "{ok,[{name,\"alice\"},admin,{email,undefined}]}".parse().unwrap();
+//! let user = User { name: "alice".to_owned(), admin: true, email: None };
+//! assert_eq!(de::from_term::<Reply>(&term).unwrap(), Reply::Ok(user));
+//!
+//! let term: Term = "{error,<<\"not found\">>}".parse().unwrap();
+//! assert_eq!(
+//!     de::from_term::<Reply>(&term).unwrap(),
+//!     Reply::Error("not found".to_owned())
+//! );
+//! ```
+use super::*;
+use crate::convert::TryAsRef;
+use num::bigint::ToBigInt;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use std::error;
+
+/// Errors which can occur when deserializing a value
+#[derive(Debug)]
+pub enum Error {
+    Decode(Box<DecodeError>),
+    UnexpectedType { value: Box<Term>, expected: String },
+    Custom(String),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Decode(ref x) => x.fmt(f),
+            Error::UnexpectedType {
+                ref value,
+                ref expected,
+            } => write!(f, "{} is not {}", value, expected),
+            Error::Custom(ref x) => x.fmt(f),
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Decode(_) => "Cannot decode the term",
+            Error::UnexpectedType { .. } => "Unexpected term type",
+            Error::Custom(ref x) => x,
+        }
+    }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Decode(ref x) => Some(&**x),
+            _ => None,
+        }
+    }
+}
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::Decode(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Deserializes a value from a term.
+pub fn from_term<'de, T: de::Deserialize<'de>>(term: &'de Term) -> Result<T> {
+    T::deserialize(Deserializer::new(term))
+}
+
+/// Decodes a term from `reader` and deserializes a value from it.
+pub fn from_reader<R: io::Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+    let term = Term::decode(reader)?;
+    from_term(&term)
+}
+
+/// Decodes a term from `bytes` and deserializes a value from it.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    from_reader(bytes)
+}
+
+/// A deserializer which reads values from a borrowed `Term`.
+#[derive(Debug, Clone, Copy)]
+pub struct Deserializer<'de> {
+    term: &'de Term,
+}
+impl<'de> Deserializer<'de> {
+    pub fn new(term: &'de Term) -> Self {
+        Deserializer { term }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        Err(Error::UnexpectedType {
+            value: Box::new(self.term.clone()),
+            expected: expected.to_owned(),
+        })
+    }
+
+    fn atom(&self) -> Option<&'de str> {
+        let atom: Option<&'de Atom> = self.term.try_as_ref();
+        atom.map(|x| x.name.as_str())
+    }
+
+    fn string(&self) -> Option<Str<'de>> {
+        match *self.term {
+            Term::Atom(ref x) => Some(Str::Borrowed(&x.name)),
+            Term::Binary(ref x) => str::from_utf8(&x.bytes).ok().map(Str::Borrowed),
+            Term::List(ref x) => x
+                .elements
+                .iter()
+                .map(|e| e.to_u32().and_then(std::char::from_u32))
+                .collect::<Option<String>>()
+                .map(Str::Owned),
+            _ => None,
+        }
+    }
+
+    fn elements(&self) -> Option<&'de [Term]> {
+        match *self.term {
+            Term::List(ref x) => Some(&x.elements),
+            Term::Tuple(ref x) => Some(&x.elements),
+            _ => None,
+        }
+    }
+
+    fn entries(&self) -> Option<Entries<'de>> {
+        match *self.term {
+            Term::Map(ref x) => Some(Entries::Map(x.entries.iter())),
+            Term::List(ref x) if x.elements.iter().all(is_proplist_entry) => {
+                Some(Entries::Proplist(x.elements.iter()))
+            }
+            _ => None,
+        }
+    }
+}
+
+enum Str<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+fn is_proplist_entry(term: &Term) -> bool {
+    match *term {
+        Term::Atom(_) => true,
+        Term::Tuple(ref x) => x.elements.len() == 2,
+        _ => false,
+    }
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $to:ident, $expected:expr) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match self.term.$to() {
+                Some(x) => visitor.$visit(x),
+                None => self.unexpected($expected),
+            }
+        }
+    };
+}
+
+// Integers are converted through `BigInt`, as `ToPrimitive::to_u64` wraps negative
+// fix integers around.
+macro_rules! deserialize_integer {
+    ($method:ident, $visit:ident, $to:ident, $expected:expr) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match self.term.to_bigint().and_then(|n| n.$to()) {
+                Some(x) => visitor.$visit(x),
+                None => self.unexpected($expected),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match *self.term {
+            Term::Atom(ref x) => match x.name.as_str() {
+                "true" => visitor.visit_bool(true),
+                "false" => visitor.visit_bool(false),
+                "undefined" | "nil" => visitor.visit_none(),
+                name => visitor.visit_borrowed_str(name),
+            },
+            Term::FixInteger(ref x) => visitor.visit_i32(x.value),
+            Term::BigInteger(ref x) => {
+                if let Some(n) = x.value.to_i64() {
+                    visitor.visit_i64(n)
+                } else if let Some(n) = x.value.to_u64() {
+                    visitor.visit_u64(n)
+                } else if let Some(n) = x.value.to_i128() {
+                    visitor.visit_i128(n)
+                } else if let Some(n) = x.value.to_u128() {
+                    visitor.visit_u128(n)
+                } else {
+                    self.unexpected("an integer within the 128-bit range")
+                }
+            }
+            Term::Float(ref x) => visitor.visit_f64(x.value),
+            Term::Binary(ref x) => match str::from_utf8(&x.bytes) {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(_) => visitor.visit_borrowed_bytes(&x.bytes),
+            },
+            Term::List(_) | Term::Tuple(_) => self.deserialize_seq(visitor),
+            Term::Map(_) => self.deserialize_map(visitor),
+            _ => self.unexpected("a term supported by serde"),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.atom() {
+            Some("true") => visitor.visit_bool(true),
+            Some("false") => visitor.visit_bool(false),
+            _ => self.unexpected("a boolean"),
+        }
+    }
+
+    deserialize_integer!(deserialize_i8, visit_i8, to_i8, "an i8");
+    deserialize_integer!(deserialize_i16, visit_i16, to_i16, "an i16");
+    deserialize_integer!(deserialize_i32, visit_i32, to_i32, "an i32");
+    deserialize_integer!(deserialize_i64, visit_i64, to_i64, "an i64");
+    deserialize_integer!(deserialize_u8, visit_u8, to_u8, "a u8");
+    deserialize_integer!(deserialize_u16, visit_u16, to_u16, "a u16");
+    deserialize_integer!(deserialize_u32, visit_u32, to_u32, "a u32");
+    deserialize_integer!(deserialize_u64, visit_u64, to_u64, "a u64");
+    deserialize_number!(deserialize_f32, visit_f32, to_f32, "a number");
+    deserialize_number!(deserialize_f64, visit_f64, to_f64, "a number");
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if let Some(c) = self.term.to_u32().and_then(std::char::from_u32) {
+            return visitor.visit_char(c);
+        }
+        let s = match self.string() {
+            Some(Str::Borrowed(s)) => s.to_owned(),
+            Some(Str::Owned(s)) => s,
+            None => String::new(),
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => self.unexpected("a character"),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.string() {
+            Some(Str::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Some(Str::Owned(s)) => visitor.visit_string(s),
+            None => self.unexpected("a string"),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match *self.term {
+            Term::Binary(ref x) => visitor.visit_borrowed_bytes(&x.bytes),
+            Term::List(ref x) => match x.elements.iter().map(|e| e.to_u8()).collect() {
+                Some(bytes) => visitor.visit_byte_buf(bytes),
+                None => self.unexpected("a binary"),
+            },
+            _ => self.unexpected("a binary"),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.atom() {
+            Some("undefined") | Some("nil") => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match *self.term {
+            Term::Tuple(ref x) if x.elements.is_empty() => visitor.visit_unit(),
+            _ => self.unexpected("an empty tuple"),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        match *self.term {
+            Term::Map(ref x) if x.entries.is_empty() => visitor.visit_unit(),
+            Term::Tuple(ref x) if x.elements.len() == 1 && is_atom(&x.elements[0], name) => {
+                visitor.visit_unit()
+            }
+            _ => self.unexpected(&format!("an empty map or {{{}}}", name)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match *self.term {
+            Term::Binary(ref x) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(x.bytes.iter().cloned()))
+            }
+            _ => match self.elements() {
+                Some(elements) => visitor.visit_seq(SeqAccess(elements.iter())),
+                None => self.unexpected("a list or a tuple"),
+            },
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        match self.elements() {
+            Some(elements) if elements.len() == len => {
+                visitor.visit_seq(SeqAccess(elements.iter()))
+            }
+            _ => self.unexpected(&format!("a tuple of size {}", len)),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.entries() {
+            Some(entries) => visitor.visit_map(MapAccess {
+                entries,
+                value: None,
+                skip_struct_key: false,
+            }),
+            None => self.unexpected("a map or a proplist"),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match *self.term {
+            Term::Tuple(ref x)
+                if x.elements.len() == fields.len() + 1 && is_atom(&x.elements[0], name) =>
+            {
+                visitor.visit_seq(SeqAccess(x.elements[1..].iter()))
+            }
+            _ => match self.entries() {
+                Some(entries) => visitor.visit_map(MapAccess {
+                    entries,
+                    value: None,
+                    skip_struct_key: !fields.contains(&"__struct__"),
+                }),
+                None => self.unexpected(&format!("a map, a proplist or a #{} record", name)),
+            },
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match *self.term {
+            Term::Atom(_) => visitor.visit_enum(EnumAccess {
+                tag: self.term,
+                payload: &[],
+            }),
+            Term::Tuple(ref x) if matches!(x.elements.first(), Some(Term::Atom(_))) => visitor
+                .visit_enum(EnumAccess {
+                    tag: &x.elements[0],
+                    payload: &x.elements[1..],
+                }),
+            _ => self.unexpected("an atom or a tagged tuple"),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+}
+
+fn is_atom(term: &Term, name: &str) -> bool {
+    match *term {
+        Term::Atom(ref x) => x.name == name,
+        _ => false,
+    }
+}
+
+struct SeqAccess<'de>(std::slice::Iter<'de, Term>);
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.0.next() {
+            Some(term) => seed.deserialize(Deserializer::new(term)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+enum Entries<'de> {
+    Map(std::slice::Iter<'de, (Term, Term)>),
+    Proplist(std::slice::Iter<'de, Term>),
+}
+
+struct MapAccess<'de> {
+    entries: Entries<'de>,
+    /// The value of the current entry, or `None` for the implicit `true` of a bare atom.
+    value: Option<&'de Term>,
+    /// Whether the `__struct__` key of Elixir structs is skipped.
+    skip_struct_key: bool,
+}
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let key = match self.entries {
+            Entries::Map(ref mut iter) if self.skip_struct_key => iter
+                .find(|(k, _)| !is_atom(k, "__struct__"))
+                .map(|(k, v)| (k, Some(v))),
+            Entries::Map(ref mut iter) => iter.next().map(|(k, v)| (k, Some(v))),
+            Entries::Proplist(ref mut iter) => iter.next().map(|entry| match *entry {
+                Term::Tuple(ref x) => (&x.elements[0], Some(&x.elements[1])),
+                _ => (entry, None),
+            }),
+        };
+        match key {
+            Some((key, value)) => {
+                self.value = value;
+                seed.deserialize(Deserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer::new(value)),
+            None => seed.deserialize(true.into_deserializer()),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.entries {
+            Entries::Map(ref iter) => Some(iter.len()),
+            Entries::Proplist(ref iter) => Some(iter.len()),
+        }
+    }
+}
+
+struct EnumAccess<'de> {
+    tag: &'de Term,
+    payload: &'de [Term],
+}
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(Deserializer::new(self.tag))?;
+        Ok((variant, self))
+    }
+}
+impl<'de> EnumAccess<'de> {
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        let mut elements = vec![self.tag.clone()];
+        elements.extend(self.payload.iter().cloned());
+        Err(Error::UnexpectedType {
+            value: Box::new(Term::from(Tuple::from(elements))),
+            expected: expected.to_owned(),
+        })
+    }
+}
+impl<'de> de::VariantAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        if self.payload.is_empty() {
+            Ok(())
+        } else {
+            self.unexpected("a unit variant")
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        match *self.payload {
+            [ref value] => seed.deserialize(Deserializer::new(value)),
+            _ => self.unexpected("a tuple of size 2"),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        if self.payload.len() == len {
+            visitor.visit_seq(SeqAccess(self.payload.iter()))
+        } else {
+            self.unexpected(&format!("a tuple of size {}", len + 1))
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match *self.payload {
+            [ref value] if fields.len() != 1 || is_map_like(value) => {
+                de::Deserializer::deserialize_map(Deserializer::new(value), visitor)
+            }
+            _ if self.payload.len() == fields.len() => {
+                visitor.visit_seq(SeqAccess(self.payload.iter()))
+            }
+            _ => self.unexpected(&format!("a tuple of size 2 or {}", fields.len() + 1)),
+        }
+    }
+}
+
+fn is_map_like(term: &Term) -> bool {
+    Deserializer::new(term).entries().is_some()
+}
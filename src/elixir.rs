@@ -0,0 +1,475 @@
+//! Formatter which writes terms like Elixir's `inspect/2`.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::Term;
+//!
+//! let term: Term = "{ok,#{name => <<\"alice\">>,roles => [admin]},[{timeout,5000}],\"abc\"}"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(
+//!     term.inspect().to_string(),
+//!     "{:ok, %{name: \"alice\", roles: [:admin]}, [timeout: 5000], ~c\"abc\"}"
+//! );
+//!
+//! let user: Term = "#{'__struct__' => 'Elixir.MyApp.User',id => 1}".parse().unwrap();
+//! assert_eq!(user.inspect().to_string(), "%MyApp.User{id: 1}");
+//! assert_eq!(
+//!     user.inspect().structs(false).to_string(),
+//!     "%{__struct__: MyApp.User, id: 1}"
+//! );
+//! ```
+use super::*;
+use crate::convert::FromTerm;
+use std::fmt::Write;
+
+/// A term wrapper whose `Display` implementation writes the term in the Elixir syntax.
+#[derive(Debug, Clone)]
+pub struct Inspect<'a> {
+    term: &'a Term,
+    structs: bool,
+}
+impl<'a> Inspect<'a> {
+    /// Makes a new formatter which writes maps carrying a `__struct__` key as structs.
+    pub fn new(term: &'a Term) -> Self {
+        Inspect {
+            term,
+            structs: true,
+        }
+    }
+
+    /// Sets whether maps carrying a `__struct__` key are written as `%Module{...}`
+    /// (`true`, the default) or as plain maps (`false`).
+    pub fn structs(mut self, structs: bool) -> Self {
+        self.structs = structs;
+        self
+    }
+
+    fn write<W: Write>(&self, w: &mut W, term: &Term) -> fmt::Result {
+        match *term {
+            Term::Atom(ref x) => write_atom(w, &x.name),
+            Term::FixInteger(ref x) => write!(w, "{}", x),
+            Term::BigInteger(ref x) => write!(w, "{}", x),
+            Term::Float(ref x) => write!(w, "{}", x),
+            Term::Pid(ref x) => write!(w, "#PID<{}.{}.{}>", x.node, x.id, x.serial),
+            Term::Port(ref x) => write!(w, "#Port<{}.{}>", x.node, x.id),
+            Term::Reference(ref x) => {
+                write!(w, "#Reference<{}", x.node)?;
+                for n in &x.id {
+                    write!(w, ".{}", n)?;
+                }
+                w.write_char('>')
+            }
+            Term::ExternalFun(ref x) => {
+                w.write_char('&')?;
+                write_module(w, &x.module.name)?;
+                w.write_char('.')?;
+                write_function(w, &x.function.name)?;
+                write!(w, "/{}", x.arity)
+            }
+            Term::InternalFun(ref x) => match *x {
+                InternalFun::Old {
+                    ref module,
+                    index,
+                    uniq,
+                    ..
+                } => {
+                    write!(w, "#Function<{}.{} in ", index, uniq)?;
+                    write_module(w, &module.name)?;
+                    w.write_char('>')
+                }
+                InternalFun::New {
+                    ref module,
+                    arity,
+                    index,
+                    uniq,
+                    ..
+                } => {
+                    use num::bigint::Sign;
+                    let uniq = BigInt::from_bytes_be(Sign::Plus, &uniq);
+                    write!(w, "#Function<{}.{}/{} in ", index, uniq, arity)?;
+                    write_module(w, &module.name)?;
+                    w.write_char('>')
+                }
+            },
+            Term::Binary(ref x) => match str::from_utf8(&x.bytes) {
+                Ok(s) if s.chars().all(is_printable) => write_string(w, s.chars(), '"'),
+                _ => write_bytes(w, &x.bytes, 8),
+            },
+            Term::BitBinary(ref x) => write_bytes(w, &x.bytes, x.tail_bits_size),
+            Term::List(ref x) if x.is_nil() => w.write_str("[]"),
+            Term::List(ref x) if is_charlist(&x.elements) => {
+                let chars = x
+                    .elements
+                    .iter()
+                    .filter_map(|e| e.to_u32().and_then(std::char::from_u32));
+                w.write_str("~c")?;
+                write_string(w, chars, '"')
+            }
+            Term::List(ref x) if is_keyword(&x.elements) => {
+                w.write_char('[')?;
+                for (i, e) in x.elements.iter().enumerate() {
+                    if i != 0 {
+                        w.write_str(", ")?;
+                    }
+                    if let Term::Tuple(ref pair) = *e {
+                        self.write_pair(w, &pair.elements[0], &pair.elements[1], true)?;
+                    }
+                }
+                w.write_char(']')
+            }
+            Term::List(ref x) => {
+                w.write_char('[')?;
+                self.write_elements(w, &x.elements)?;
+                w.write_char(']')
+            }
+            Term::ImproperList(ref x) => {
+                w.write_char('[')?;
+                self.write_elements(w, &x.elements)?;
+                w.write_str(" | ")?;
+                self.write(w, &x.last)?;
+                w.write_char(']')
+            }
+            Term::Tuple(ref x) => {
+                w.write_char('{')?;
+                self.write_elements(w, &x.elements)?;
+                w.write_char('}')
+            }
+            Term::Map(ref x) => self.write_map(w, x),
+        }
+    }
+
+    fn write_elements<W: Write>(&self, w: &mut W, elements: &[Term]) -> fmt::Result {
+        for (i, e) in elements.iter().enumerate() {
+            if i != 0 {
+                w.write_str(", ")?;
+            }
+            self.write(w, e)?;
+        }
+        Ok(())
+    }
+
+    fn write_map<W: Write>(&self, w: &mut W, map: &Map) -> fmt::Result {
+        let module = map.entries.iter().find_map(|(k, v)| match (k, v) {
+            (Term::Atom(k), Term::Atom(v)) if k.name == "__struct__" => Some(v),
+            _ => None,
+        });
+        let entries = map.entries.iter();
+        let (module, entries): (_, Vec<_>) = match module {
+            Some(module) if self.structs => (
+                Some(module),
+                entries
+                    .filter(|(k, _)| atom_name(k) != Some("__struct__"))
+                    .collect(),
+            ),
+            _ => (None, entries.collect()),
+        };
+        w.write_char('%')?;
+        if let Some(module) = module {
+            write_module(w, &module.name)?;
+        }
+        w.write_char('{')?;
+        let keyword = entries.iter().all(|(k, _)| atom_name(k).is_some());
+        for (i, (k, v)) in entries.into_iter().enumerate() {
+            if i != 0 {
+                w.write_str(", ")?;
+            }
+            self.write_pair(w, k, v, keyword)?;
+        }
+        w.write_char('}')
+    }
+
+    /// Writes a key-value pair either as `key: value` or as `key => value`.
+    fn write_pair<W: Write>(&self, w: &mut W, k: &Term, v: &Term, keyword: bool) -> fmt::Result {
+        match atom_name(k) {
+            Some(name) if keyword => {
+                if atom_needs_quotes(name) {
+                    write_string(w, name.chars(), '"')?;
+                } else {
+                    w.write_str(name)?;
+                }
+                w.write_str(": ")?;
+            }
+            _ => {
+                self.write(w, k)?;
+                w.write_str(" => ")?;
+            }
+        }
+        self.write(w, v)
+    }
+}
+impl<'a> fmt::Display for Inspect<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, self.term)
+    }
+}
+
+/// A view of a map which represents an Elixir struct, i.e. which has a `__struct__` key.
+#[derive(Debug, Clone, Copy)]
+pub struct Struct<'a> {
+    map: &'a Map,
+    module: &'a Atom,
+}
+impl<'a> Struct<'a> {
+    /// Makes a view of `map`, or returns `None` if the map is not a struct.
+    pub fn new(map: &'a Map) -> Option<Self> {
+        map.entries
+            .iter()
+            .find_map(|(k, v)| match (k, v) {
+                (Term::Atom(k), Term::Atom(v)) if k.name == "__struct__" => Some(v),
+                _ => None,
+            })
+            .map(|module| Struct { map, module })
+    }
+
+    /// Returns the module name without the `Elixir.` prefix, e.g. `MyApp.User`.
+    pub fn module(&self) -> &'a str {
+        let name = &self.module.name;
+        name.strip_prefix("Elixir.").unwrap_or(name)
+    }
+
+    /// Returns the underlying map.
+    pub fn as_map(&self) -> &'a Map {
+        self.map
+    }
+
+    /// Returns the module atom, e.g. `'Elixir.MyApp.User'`.
+    pub fn module_atom(&self) -> &'a Atom {
+        self.module
+    }
+
+    /// Returns the value of `field`.
+    pub fn get(&self, field: &str) -> Option<&'a Term> {
+        self.fields()
+            .find(|&(name, _)| name == field)
+            .map(|(_, value)| value)
+    }
+
+    /// Converts the value of `field` into a Rust value.
+    pub fn get_into<T: FromTerm>(&self, field: &str) -> Result<T, DecodeError> {
+        match self.get(field) {
+            Some(value) => T::from_term(value.clone()),
+            None => T::from_missing().ok_or_else(|| DecodeError::UnexpectedType {
+                value: Term::from(self.map.clone()),
+                expected: format!("%{}{{}} (missing field {})", self.module(), field),
+            }),
+        }
+    }
+
+    /// Returns the fields (the entries with atom keys, except `__struct__`).
+    pub fn fields(&self) -> impl Iterator<Item = (&'a str, &'a Term)> {
+        self.map
+            .entries
+            .iter()
+            .filter_map(|(k, v)| match atom_name(k) {
+                Some("__struct__") | None => None,
+                Some(name) => Some((name, v)),
+            })
+    }
+}
+
+/// A builder of struct maps.
+///
+/// # Examples
+///
+/// ```
+/// use eetf::{Term, elixir::StructBuilder};
+///
+/// let user = StructBuilder::new("MyApp.User").field("id", 1).field("admin", false).build();
+/// assert_eq!(Term::from(user).inspect().to_string(), "%MyApp.User{id: 1, admin: false}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct StructBuilder {
+    entries: Vec<(Term, Term)>,
+}
+impl StructBuilder {
+    /// Makes a new builder of a struct of `module`.
+    ///
+    /// The `Elixir.` prefix is added to the module name unless it is already there.
+    pub fn new(module: &str) -> Self {
+        let module = if module.starts_with("Elixir.") {
+            module.to_owned()
+        } else {
+            format!("Elixir.{}", module)
+        };
+        StructBuilder {
+            entries: vec![(
+                Term::from(Atom::from("__struct__")),
+                Term::from(Atom::from(module)),
+            )],
+        }
+    }
+
+    /// Adds a field.
+    pub fn field<V: Into<Term>>(mut self, name: &str, value: V) -> Self {
+        self.entries
+            .push((Term::from(Atom::from(name)), value.into()));
+        self
+    }
+
+    /// Builds the struct map.
+    pub fn build(self) -> Map {
+        Map::from(self.entries)
+    }
+}
+
+fn atom_name(term: &Term) -> Option<&str> {
+    match *term {
+        Term::Atom(ref x) => Some(&x.name),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `name` is written as an alias, e.g. `MyApp.User` for
+/// `'Elixir.MyApp.User'`.
+fn alias_name(name: &str) -> Option<&str> {
+    if name == "Elixir" {
+        return Some(name);
+    }
+    let alias = name.strip_prefix("Elixir.")?;
+    let is_alias = alias.split('.').all(|segment| {
+        let mut chars = segment.chars();
+        chars.next().is_some_and(|c| c.is_ascii_uppercase())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if is_alias {
+        Some(alias)
+    } else {
+        None
+    }
+}
+
+fn atom_needs_quotes(name: &str) -> bool {
+    let mut chars = name.chars();
+    if !chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    {
+        return true;
+    }
+    let rest = chars.as_str();
+    let rest = rest
+        .strip_suffix('?')
+        .or_else(|| rest.strip_suffix('!'))
+        .unwrap_or(rest);
+    !rest
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '@')
+}
+
+fn write_atom<W: Write>(w: &mut W, name: &str) -> fmt::Result {
+    match name {
+        "nil" | "true" | "false" => w.write_str(name),
+        _ => {
+            if let Some(alias) = alias_name(name) {
+                return w.write_str(alias);
+            }
+            w.write_char(':')?;
+            if atom_needs_quotes(name) {
+                write_string(w, name.chars(), '"')
+            } else {
+                w.write_str(name)
+            }
+        }
+    }
+}
+
+/// Writes a module name as it appears in a remote call, e.g. `Enum` or `:lists`.
+fn write_module<W: Write>(w: &mut W, name: &str) -> fmt::Result {
+    match alias_name(name) {
+        Some(alias) => w.write_str(alias),
+        None => {
+            w.write_char(':')?;
+            if atom_needs_quotes(name) {
+                write_string(w, name.chars(), '"')
+            } else {
+                w.write_str(name)
+            }
+        }
+    }
+}
+
+fn write_function<W: Write>(w: &mut W, name: &str) -> fmt::Result {
+    if atom_needs_quotes(name) {
+        write_string(w, name.chars(), '"')
+    } else {
+        w.write_str(name)
+    }
+}
+
+fn write_bytes<W: Write>(w: &mut W, bytes: &[u8], tail_bits_size: u8) -> fmt::Result {
+    w.write_str("<<")?;
+    for (i, b) in bytes.iter().enumerate() {
+        let is_last = i + 1 == bytes.len();
+        if is_last && tail_bits_size == 0 {
+            break;
+        }
+        if i != 0 {
+            w.write_str(", ")?;
+        }
+        if is_last && tail_bits_size < 8 {
+            write!(w, "{}::size({})", b, tail_bits_size)?;
+        } else {
+            write!(w, "{}", b)?;
+        }
+    }
+    w.write_str(">>")
+}
+
+/// Writes `chars` surrounded by `quote`, escaping them as in an Elixir string literal.
+fn write_string<W, I>(w: &mut W, chars: I, quote: char) -> fmt::Result
+where
+    W: Write,
+    I: IntoIterator<Item = char>,
+{
+    w.write_char(quote)?;
+    let mut chars = chars.into_iter().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{b}' => w.write_str("\\v")?,
+            '\u{8}' => w.write_str("\\b")?,
+            '\u{c}' => w.write_str("\\f")?,
+            '\u{1b}' => w.write_str("\\e")?,
+            '\u{7}' => w.write_str("\\a")?,
+            '#' if chars.peek() == Some(&'{') => w.write_str("\\#")?,
+            c if c == quote => {
+                w.write_char('\\')?;
+                w.write_char(c)?;
+            }
+            c if c.is_control() => write!(w, "\\u{{{:X}}}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char(quote)
+}
+
+/// Returns `true` if `c` is printable in the sense of `String.printable?/1`.
+fn is_printable(c: char) -> bool {
+    !c.is_control()
+        || matches!(
+            c,
+            '\n' | '\r' | '\t' | '\u{b}' | '\u{8}' | '\u{c}' | '\u{1b}' | '\u{7}'
+        )
+}
+
+/// Returns `true` if `elements` is a list written as a charlist, i.e. it
+/// satisfies `List.ascii_printable?/1`.
+fn is_charlist(elements: &[Term]) -> bool {
+    elements.iter().all(|e| match e.to_u32() {
+        Some(c) => matches!(c, 0x20..=0x7e | 0x7..=0xd | 0x1b),
+        None => false,
+    })
+}
+
+/// Returns `true` if `elements` is a keyword list (a list of `{atom, value}` pairs).
+fn is_keyword(elements: &[Term]) -> bool {
+    elements.iter().all(|e| match *e {
+        Term::Tuple(ref x) => x.elements.len() == 2 && atom_name(&x.elements[0]).is_some(),
+        _ => false,
+    })
+}
@@ -0,0 +1,572 @@
+//! Conversion between terms and JSON values of [serde_json](https://docs.rs/serde_json).
+//!
+//! This module is available if the `json` feature is enabled.
+//!
+//! # Mapping
+//!
+//! | JSON               | Erlang                                          |
+//! |--------------------|-------------------------------------------------|
+//! | object             | map with binary (or atom, see `Keys`) keys      |
+//! | array              | list                                            |
+//! | string             | binary                                          |
+//! | integer            | integer                                         |
+//! | other numbers      | float                                           |
+//! | `true` / `false`   | `true` / `false`                                |
+//! | `null`             | `null` (or another atom, see `Options::null`)   |
+//!
+//! Other atoms are converted into strings. Terms which have no JSON counterpart
+//! (e.g. tuples or pids) are handled according to the `Mode`.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::json::{self, Mode, Options};
+//! use eetf::Term;
+//! use serde_json::json;
+//!
+//! let term = json::from_value(&json!({"id": 1, "tags": ["a"], "manager": null}));
+//! assert_eq!(
+//!     term,
+//!     "#{<<\"id\">> => 1,<<\"manager\">> => null,<<\"tags\">> => [<<\"a\">>]}".parse().unwrap()
+//! );
+//!
+//! let term: Term = "{ok,<nonode@nohost.1.2>}".parse().unwrap();
+//! assert!(json::to_value(&term).is_err());
+//!
+//! let lossy = Options::new().mode(Mode::Lossy);
+//! assert_eq!(lossy.to_value(&term).unwrap(), json!(["ok", "<nonode@nohost.1.2>"]));
+//!
+//! let tagged = Options::new().mode(Mode::Tagged);
+//! let value = tagged.to_value(&term).unwrap();
+//! assert_eq!(tagged.to_term(&value), term);
+//! ```
+use super::*;
+use num::bigint::BigInt;
+use serde_json::{Map as Object, Number, Value};
+use std::error;
+
+/// Errors which can occur when converting a term into a JSON value
+#[derive(Debug)]
+pub enum Error {
+    Unsupported { value: Box<Term> },
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Unsupported { ref value } => {
+                write!(f, "{} cannot be represented in JSON", value)
+            }
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Unsupported { .. } => "Term cannot be represented in JSON",
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// How the keys of JSON objects are represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keys {
+    /// Binaries (the default).
+    Binary,
+    /// Atoms.
+    Atom,
+}
+
+/// How terms without a JSON counterpart are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Such terms are an error (the default).
+    Strict,
+    /// Such terms are converted into the closest JSON value:
+    /// tuples and improper lists into arrays, big integers and pids, ports, references
+    /// and funs into strings, invalid UTF-8 binaries into strings with replacement characters,
+    /// bitstrings into arrays of bytes, and other map keys into strings.
+    Lossy,
+    /// Such terms are converted into objects which are converted back into the same terms,
+    /// e.g. `{"$tuple": [...]}` or `{"$atom": "..."}`.
+    ///
+    /// Atoms (except booleans and the `null` atom) and maps whose keys are not all of the
+    /// configured type or not in sorted order (as JSON objects sort their keys) are tagged too,
+    /// so that `to_term(to_value(t)) == t` holds for any term except maps whose only key is
+    /// a string starting with `$`.
+    Tagged,
+}
+
+/// Conversion options.
+#[derive(Debug, Clone)]
+pub struct Options {
+    keys: Keys,
+    null: String,
+    mode: Mode,
+}
+impl Options {
+    /// Makes new options with the default settings.
+    pub fn new() -> Self {
+        Options {
+            keys: Keys::Binary,
+            null: "null".to_owned(),
+            mode: Mode::Strict,
+        }
+    }
+
+    /// Sets the representation of object keys.
+    pub fn keys(mut self, keys: Keys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Sets the atom which represents `null` (e.g. `nil` for Elixir or `undefined`).
+    pub fn null(mut self, atom: &str) -> Self {
+        self.null = atom.to_owned();
+        self
+    }
+
+    /// Sets the conversion mode of terms without a JSON counterpart.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Converts a JSON value into a term.
+    pub fn to_term(&self, value: &Value) -> Term {
+        match *value {
+            Value::Null => atom(&self.null),
+            Value::Bool(x) => atom(if x { "true" } else { "false" }),
+            Value::Number(ref x) => number_term(x),
+            Value::String(ref x) => Term::from(Binary::from(x.as_bytes())),
+            Value::Array(ref x) => Term::from(List::from(self.to_terms(x))),
+            Value::Object(ref x) => {
+                if self.mode == Mode::Tagged {
+                    if let Some(term) = self.tagged_term(x) {
+                        return term;
+                    }
+                }
+                Term::from(Map::from(
+                    x.iter()
+                        .map(|(k, v)| (self.key_term(k), self.to_term(v)))
+                        .collect::<Vec<_>>(),
+                ))
+            }
+        }
+    }
+
+    /// Converts a term into a JSON value.
+    pub fn to_value(&self, term: &Term) -> Result<Value> {
+        match *term {
+            Term::Atom(ref x) => Ok(match x.name.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                name if name == self.null => Value::Null,
+                name if self.mode == Mode::Tagged => tagged("$atom", Value::from(name)),
+                name => Value::from(name),
+            }),
+            Term::FixInteger(ref x) => Ok(Value::from(x.value)),
+            Term::BigInteger(ref x) => {
+                if let Some(n) = x.value.to_i64() {
+                    Ok(Value::from(n))
+                } else if let Some(n) = x.value.to_u64() {
+                    Ok(Value::from(n))
+                } else {
+                    self.fallback(
+                        term,
+                        || Value::from(x.value.to_string()),
+                        || Ok(tagged("$bigint", Value::from(x.value.to_string()))),
+                    )
+                }
+            }
+            Term::Float(ref x) => Number::from_f64(x.value)
+                .map(Value::Number)
+                .ok_or_else(|| unsupported(term)),
+            Term::Binary(ref x) => match str::from_utf8(&x.bytes) {
+                Ok(s) => Ok(Value::from(s)),
+                Err(_) => self.fallback(
+                    term,
+                    || Value::from(String::from_utf8_lossy(&x.bytes).into_owned()),
+                    || Ok(tagged("$binary", Value::from(x.bytes.clone()))),
+                ),
+            },
+            Term::BitBinary(ref x) => self.fallback(
+                term,
+                || Value::from(x.bytes.clone()),
+                || {
+                    let mut object = Object::new();
+                    object.insert("$bitstring".to_owned(), Value::from(x.bytes.clone()));
+                    object.insert("bits".to_owned(), Value::from(x.tail_bits_size));
+                    Ok(Value::Object(object))
+                },
+            ),
+            Term::List(ref x) => self.to_values(&x.elements).map(Value::Array),
+            Term::ImproperList(ref x) => {
+                let lossy = || {
+                    let mut elements = x.elements.clone();
+                    elements.push((*x.last).clone());
+                    elements
+                };
+                match self.mode {
+                    Mode::Strict => Err(unsupported(term)),
+                    Mode::Lossy => self.to_values(&lossy()).map(Value::Array),
+                    Mode::Tagged => {
+                        let mut object = Object::new();
+                        object.insert(
+                            "$improper_list".to_owned(),
+                            Value::Array(self.to_values(&x.elements)?),
+                        );
+                        object.insert("tail".to_owned(), self.to_value(&x.last)?);
+                        Ok(Value::Object(object))
+                    }
+                }
+            }
+            Term::Tuple(ref x) => match self.mode {
+                Mode::Strict => Err(unsupported(term)),
+                Mode::Lossy => self.to_values(&x.elements).map(Value::Array),
+                Mode::Tagged => Ok(tagged("$tuple", Value::Array(self.to_values(&x.elements)?))),
+            },
+            Term::Map(ref x) => self.map_value(term, x),
+            Term::Pid(ref x) => self.fallback(
+                term,
+                || Value::from(term.to_string()),
+                || Ok(tagged("$pid", pid_object(x))),
+            ),
+            Term::Port(ref x) => self.fallback(
+                term,
+                || Value::from(term.to_string()),
+                || {
+                    let mut object = Object::new();
+                    object.insert("node".to_owned(), Value::from(x.node.name.as_str()));
+                    object.insert("id".to_owned(), Value::from(x.id));
+                    object.insert("creation".to_owned(), Value::from(x.creation));
+                    Ok(tagged("$port", Value::Object(object)))
+                },
+            ),
+            Term::Reference(ref x) => self.fallback(
+                term,
+                || Value::from(term.to_string()),
+                || {
+                    let mut object = Object::new();
+                    object.insert("node".to_owned(), Value::from(x.node.name.as_str()));
+                    object.insert("id".to_owned(), Value::from(x.id.clone()));
+                    object.insert("creation".to_owned(), Value::from(x.creation));
+                    Ok(tagged("$ref", Value::Object(object)))
+                },
+            ),
+            Term::ExternalFun(ref x) => self.fallback(
+                term,
+                || Value::from(term.to_string()),
+                || {
+                    let mut object = Object::new();
+                    object.insert("module".to_owned(), Value::from(x.module.name.as_str()));
+                    object.insert("function".to_owned(), Value::from(x.function.name.as_str()));
+                    object.insert("arity".to_owned(), Value::from(x.arity));
+                    Ok(tagged("$fun", Value::Object(object)))
+                },
+            ),
+            Term::InternalFun(ref x) => self.fallback(
+                term,
+                || Value::from(term.to_string()),
+                || self.internal_fun_value(x),
+            ),
+        }
+    }
+
+    fn to_terms(&self, values: &[Value]) -> Vec<Term> {
+        values.iter().map(|v| self.to_term(v)).collect()
+    }
+
+    fn to_values(&self, terms: &[Term]) -> Result<Vec<Value>> {
+        terms.iter().map(|t| self.to_value(t)).collect()
+    }
+
+    fn key_term(&self, key: &str) -> Term {
+        match self.keys {
+            Keys::Binary => Term::from(Binary::from(key.as_bytes())),
+            Keys::Atom => atom(key),
+        }
+    }
+
+    /// Returns the object key for `key`, if it is a string-like term.
+    fn key_string<'a>(&self, key: &'a Term) -> Option<&'a str> {
+        match (key, self.keys, self.mode) {
+            (Term::Atom(x), Keys::Atom, _) => Some(&x.name),
+            (Term::Binary(x), Keys::Binary, _) => str::from_utf8(&x.bytes).ok(),
+            (_, _, Mode::Tagged) => None,
+            (Term::Atom(x), _, _) => Some(&x.name),
+            (Term::Binary(x), _, _) => str::from_utf8(&x.bytes).ok(),
+            _ => None,
+        }
+    }
+
+    fn map_value(&self, term: &Term, map: &Map) -> Result<Value> {
+        let keys = map
+            .entries
+            .iter()
+            .map(|(k, _)| self.key_string(k))
+            .collect::<Option<Vec<_>>>();
+        let keys = keys.filter(|keys| {
+            self.mode != Mode::Tagged || keys.windows(2).all(|pair| pair[0] < pair[1])
+        });
+        match (keys, self.mode) {
+            (Some(keys), _) => {
+                let mut object = Object::new();
+                for (k, (_, v)) in keys.into_iter().zip(&map.entries) {
+                    object.insert(k.to_owned(), self.to_value(v)?);
+                }
+                Ok(Value::Object(object))
+            }
+            (None, Mode::Strict) => Err(unsupported(term)),
+            (None, Mode::Lossy) => {
+                let mut object = Object::new();
+                for (k, v) in &map.entries {
+                    let k = match self.key_string(k) {
+                        Some(k) => k.to_owned(),
+                        None => k.to_string(),
+                    };
+                    object.insert(k, self.to_value(v)?);
+                }
+                Ok(Value::Object(object))
+            }
+            (None, Mode::Tagged) => {
+                let entries = map
+                    .entries
+                    .iter()
+                    .map(|(k, v)| Ok(Value::Array(vec![self.to_value(k)?, self.to_value(v)?])))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(tagged("$map", Value::Array(entries)))
+            }
+        }
+    }
+
+    fn internal_fun_value(&self, fun: &InternalFun) -> Result<Value> {
+        let mut object = Object::new();
+        match *fun {
+            InternalFun::Old {
+                ref module,
+                ref pid,
+                ref free_vars,
+                index,
+                uniq,
+            } => {
+                object.insert("module".to_owned(), Value::from(module.name.as_str()));
+                object.insert("pid".to_owned(), pid_object(pid));
+                object.insert(
+                    "free_vars".to_owned(),
+                    Value::Array(self.to_values(free_vars)?),
+                );
+                object.insert("index".to_owned(), Value::from(index));
+                object.insert("uniq".to_owned(), Value::from(uniq));
+            }
+            InternalFun::New {
+                ref module,
+                arity,
+                ref pid,
+                ref free_vars,
+                index,
+                ref uniq,
+                old_index,
+                old_uniq,
+            } => {
+                object.insert("module".to_owned(), Value::from(module.name.as_str()));
+                object.insert("arity".to_owned(), Value::from(arity));
+                object.insert("pid".to_owned(), pid_object(pid));
+                object.insert(
+                    "free_vars".to_owned(),
+                    Value::Array(self.to_values(free_vars)?),
+                );
+                object.insert("index".to_owned(), Value::from(index));
+                object.insert("uniq".to_owned(), Value::from(&uniq[..]));
+                object.insert("old_index".to_owned(), Value::from(old_index));
+                object.insert("old_uniq".to_owned(), Value::from(old_uniq));
+            }
+        }
+        Ok(tagged("$internal_fun", Value::Object(object)))
+    }
+
+    /// Converts a term without a JSON counterpart according to the mode.
+    fn fallback<L, T>(&self, term: &Term, lossy: L, tagged: T) -> Result<Value>
+    where
+        L: FnOnce() -> Value,
+        T: FnOnce() -> Result<Value>,
+    {
+        match self.mode {
+            Mode::Strict => Err(unsupported(term)),
+            Mode::Lossy => Ok(lossy()),
+            Mode::Tagged => tagged(),
+        }
+    }
+
+    /// Converts an object made by the `Tagged` mode back into a term.
+    fn tagged_term(&self, object: &Object<String, Value>) -> Option<Term> {
+        let (tag, value) = object.iter().find(|(k, _)| k.starts_with('$'))?;
+        let len = match tag.as_str() {
+            "$bitstring" | "$improper_list" => 2,
+            _ => 1,
+        };
+        if object.len() != len {
+            return None;
+        }
+        let term = match tag.as_str() {
+            "$atom" => atom(value.as_str()?),
+            "$bigint" => Term::from(BigInteger {
+                value: value.as_str()?.parse::<BigInt>().ok()?,
+            }),
+            "$binary" => Term::from(Binary::from(bytes(value)?)),
+            "$bitstring" => Term::from(BitBinary::from((bytes(value)?, get_int(object, "bits")?))),
+            "$tuple" => Term::from(Tuple::from(self.to_terms(value.as_array()?))),
+            "$improper_list" => Term::from(ImproperList::from((
+                self.to_terms(value.as_array()?),
+                self.to_term(object.get("tail")?),
+            ))),
+            "$map" => Term::from(Map::from(
+                value
+                    .as_array()?
+                    .iter()
+                    .map(|entry| match entry.as_array()?.as_slice() {
+                        [k, v] => Some((self.to_term(k), self.to_term(v))),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?,
+            )),
+            "$pid" => Term::from(pid_from_object(value.as_object()?)?),
+            "$port" => {
+                let x = value.as_object()?;
+                Term::from(Port {
+                    node: Atom::from(x.get("node")?.as_str()?),
+                    id: get_int(x, "id")?,
+                    creation: get_int(x, "creation")?,
+                })
+            }
+            "$ref" => {
+                let x = value.as_object()?;
+                Term::from(Reference {
+                    node: Atom::from(x.get("node")?.as_str()?),
+                    id: x
+                        .get("id")?
+                        .as_array()?
+                        .iter()
+                        .map(|n| n.as_u64().and_then(|n| u32::try_from(n).ok()))
+                        .collect::<Option<Vec<_>>>()?,
+                    creation: get_int(x, "creation")?,
+                })
+            }
+            "$fun" => {
+                let x = value.as_object()?;
+                Term::from(ExternalFun {
+                    module: Atom::from(x.get("module")?.as_str()?),
+                    function: Atom::from(x.get("function")?.as_str()?),
+                    arity: get_int(x, "arity")?,
+                })
+            }
+            "$internal_fun" => Term::from(self.internal_fun_term(value.as_object()?)?),
+            _ => return None,
+        };
+        Some(term)
+    }
+
+    fn internal_fun_term(&self, x: &Object<String, Value>) -> Option<InternalFun> {
+        let module = Atom::from(x.get("module")?.as_str()?);
+        let pid = pid_from_object(x.get("pid")?.as_object()?)?;
+        let free_vars = self.to_terms(x.get("free_vars")?.as_array()?);
+        if x.contains_key("arity") {
+            let uniq = bytes(x.get("uniq")?)?;
+            Some(InternalFun::New {
+                module,
+                arity: get_int(x, "arity")?,
+                pid,
+                free_vars,
+                index: get_int(x, "index")?,
+                uniq: <[u8; 16]>::try_from(uniq.as_slice()).ok()?,
+                old_index: get_int(x, "old_index")?,
+                old_uniq: get_int(x, "old_uniq")?,
+            })
+        } else {
+            Some(InternalFun::Old {
+                module,
+                pid,
+                free_vars,
+                index: get_int(x, "index")?,
+                uniq: get_int(x, "uniq")?,
+            })
+        }
+    }
+}
+impl Default for Options {
+    fn default() -> Self {
+        Options::new()
+    }
+}
+
+/// Converts a JSON value into a term with the default options.
+pub fn from_value(value: &Value) -> Term {
+    Options::new().to_term(value)
+}
+
+/// Converts a term into a JSON value with the default options.
+pub fn to_value(term: &Term) -> Result<Value> {
+    Options::new().to_value(term)
+}
+
+fn atom(name: &str) -> Term {
+    Term::from(Atom::from(name))
+}
+
+fn unsupported(term: &Term) -> Error {
+    Error::Unsupported {
+        value: Box::new(term.clone()),
+    }
+}
+
+fn tagged(tag: &str, value: Value) -> Value {
+    let mut object = Object::new();
+    object.insert(tag.to_owned(), value);
+    Value::Object(object)
+}
+
+fn number_term(n: &Number) -> Term {
+    if let Some(n) = n.as_i64() {
+        match i32::try_from(n) {
+            Ok(n) => Term::from(FixInteger::from(n)),
+            Err(_) => Term::from(BigInteger::from(n)),
+        }
+    } else if let Some(n) = n.as_u64() {
+        Term::from(BigInteger::from(n))
+    } else {
+        Term::from(Float {
+            value: n.as_f64().unwrap_or(f64::NAN),
+        })
+    }
+}
+
+fn pid_object(pid: &Pid) -> Value {
+    let mut object = Object::new();
+    object.insert("node".to_owned(), Value::from(pid.node.name.as_str()));
+    object.insert("id".to_owned(), Value::from(pid.id));
+    object.insert("serial".to_owned(), Value::from(pid.serial));
+    object.insert("creation".to_owned(), Value::from(pid.creation));
+    Value::Object(object)
+}
+
+fn pid_from_object(x: &Object<String, Value>) -> Option<Pid> {
+    Some(Pid {
+        node: Atom::from(x.get("node")?.as_str()?),
+        id: get_int(x, "id")?,
+        serial: get_int(x, "serial")?,
+        creation: get_int(x, "creation")?,
+    })
+}
+
+fn get_int<T: TryFrom<i64>>(object: &Object<String, Value>, key: &str) -> Option<T> {
+    T::try_from(object.get(key)?.as_i64()?).ok()
+}
+
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
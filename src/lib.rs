@@ -24,6 +24,18 @@
 //! assert_eq!(vec![131, 100, 0, 3, 102, 111, 111], buf);
 //! ```
 //!
+//! Parses a term written in the Erlang syntax:
+//!
+//! ```
+//! use eetf::{Term, Tuple, Atom, FixInteger};
+//!
+//! let term: Term = "{ok, 10}".parse().unwrap();
+//! assert_eq!(term, Term::from(Tuple::from(vec![
+//!     Term::from(Atom::from("ok")),
+//!     Term::from(FixInteger::from(10)),
+//! ])));
+//! ```
+//!
 //! # Reference
 //!
 //! - [Erlang External Term Format](http://erlang.org/doc/apps/erts/erl_ext_dist.html)
@@ -33,18 +45,68 @@ extern crate libflate;
 extern crate num;
 
 use num::bigint::BigInt;
+use num::traits::ToPrimitive;
 use std::convert::{From, TryFrom};
 use std::fmt;
 use std::io;
+use std::str;
 
+#[cfg(feature = "cbor")]
+pub mod cbor;
 mod codec;
 pub mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod elixir;
+#[cfg(feature = "json")]
+pub mod json;
+#[doc(hidden)]
+pub mod macros;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+mod parse;
 pub mod pattern;
+pub mod pretty;
+pub mod proplist;
+pub mod query;
+pub mod record;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod spec;
+mod syntax;
 
 pub use crate::codec::DecodeError;
 pub use crate::codec::DecodeResult;
 pub use crate::codec::EncodeError;
 pub use crate::codec::EncodeResult;
+pub use crate::convert::{FromTerm, ToTerm};
+pub use crate::parse::ParseError;
+#[cfg(feature = "derive")]
+pub use eetf_derive::{FromTerm, ToTerm};
+
+/// # Examples
+///
+/// ```
+/// use eetf::pattern::U8;
+/// use eetf::{term_match, Term};
+///
+/// let term: Term = "{ok,#{name => alice,age => 30}}".parse().unwrap();
+/// let result = term_match!(term,
+///     {ok, #{name := Name, age := Age @ U8}} => format!("{} is {}", Name, Age),
+///     {error, Reason} => format!("error: {}", Reason),
+/// );
+/// assert_eq!("alice is 30", result.unwrap());
+/// ```
+///
+/// Map keys cannot be variables:
+///
+/// ```compile_fail
+/// # use eetf::{term_match, Term};
+/// # let term: Term = "#{}".parse().unwrap();
+/// let result = term_match!(term, #{Key := _} => ());
+/// ```
+#[cfg(feature = "derive")]
+pub use eetf_derive::term_match;
 
 /// Term.
 #[derive(Debug, PartialEq, Clone)]
@@ -82,6 +144,68 @@ impl Term {
     {
         pattern.try_match(self)
     }
+
+    /// Matches the term against `pattern`, consuming the term.
+    ///
+    /// Unlike [`Term::as_match`], the output and the error do not borrow the term:
+    /// the sub-terms in the output are moved out of the term, and the other borrowed
+    /// parts such as `&str` are copied (see [`pattern::IntoOwned`]).
+    ///
+    /// ```
+    /// use eetf::Term;
+    /// use eetf::pattern::{any, U8};
+    ///
+    /// let term: Term = "{ok,<<\"alice\">>,30}".parse().unwrap();
+    /// let (_, name, age): (String, eetf::Binary, u8) = term
+    ///     .into_match(("ok", any::<eetf::Binary>(), U8))
+    ///     .unwrap();
+    /// assert_eq!((&b"alice"[..], 30), (&name.bytes[..], age));
+    /// ```
+    pub fn into_match<P, T>(mut self, pattern: P) -> Result<T, pattern::OwnedUnmatch>
+    where
+        P: for<'a> pattern::Pattern<'a>,
+        for<'a> <P as pattern::Pattern<'a>>::Output: pattern::IntoOwned<Owned = T>,
+    {
+        let (deferred, marks) = match pattern.try_match(&self) {
+            Ok(output) => {
+                let mut marks = pattern::Marks::new(&self);
+                (pattern::IntoOwned::defer(output, &mut marks), marks)
+            }
+            Err(e) => return Err(pattern::OwnedUnmatch::from(e)),
+        };
+        let mut taken = marks.take_from(&mut self);
+        Ok(deferred(&mut taken))
+    }
+
+    /// Returns the sub-terms selected by the query string (see the `query` module).
+    pub fn query(&self, query: &str) -> Result<Vec<&Term>, ParseError> {
+        Ok(query.parse::<query::Query>()?.select(self))
+    }
+
+    /// Returns mutable references to the sub-terms selected by the query string
+    /// (see the `query` module).
+    pub fn query_mut(&mut self, query: &str) -> Result<Vec<&mut Term>, ParseError> {
+        Ok(query.parse::<query::Query>()?.select_mut(self))
+    }
+
+    /// Returns a wrapper which pretty prints the term (see the `pretty` module).
+    pub fn pretty(&self) -> pretty::Pretty<'_> {
+        pretty::Pretty::new(self)
+    }
+
+    /// Returns a wrapper which writes the term like Elixir's `inspect/2`
+    /// (see the `elixir` module).
+    pub fn inspect(&self) -> elixir::Inspect<'_> {
+        elixir::Inspect::new(self)
+    }
+}
+impl str::FromStr for Term {
+    type Err = ParseError;
+
+    /// Parses a term written in the Erlang literal syntax.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::Parser::new(s).parse()
+    }
 }
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -188,11 +312,7 @@ pub struct Atom {
 }
 impl fmt::Display for Atom {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "'{}'",
-            self.name.replace("\\", "\\\\").replace("'", "\\'")
-        )
+        syntax::write_atom(f, &self.name)
     }
 }
 impl<'a> From<&'a str> for Atom {
@@ -334,6 +454,20 @@ impl From<usize> for BigInteger {
         }
     }
 }
+impl From<i128> for BigInteger {
+    fn from(value: i128) -> Self {
+        BigInteger {
+            value: BigInt::from(value),
+        }
+    }
+}
+impl From<u128> for BigInteger {
+    fn from(value: u128) -> Self {
+        BigInteger {
+            value: BigInt::from(value),
+        }
+    }
+}
 impl<'a> From<&'a FixInteger> for BigInteger {
     fn from(i: &FixInteger) -> Self {
         BigInteger {
@@ -350,7 +484,7 @@ pub struct Float {
 }
 impl fmt::Display for Float {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.value)
+        syntax::write_float(f, self.value)
     }
 }
 impl TryFrom<f32> for Float {
@@ -632,9 +766,26 @@ impl List {
     pub fn is_nil(&self) -> bool {
         self.elements.is_empty()
     }
+
+    /// Returns `true` if the list is a non-empty list of printable character codes,
+    /// i.e. it is displayed as a string literal.
+    pub fn is_printable(&self) -> bool {
+        syntax::is_printable_codes(self.elements.iter().map(|e| e.to_u32()))
+    }
+    /// Returns a view of the list as a property list.
+    pub fn as_proplist(&self) -> proplist::Proplist<'_> {
+        proplist::Proplist::new(self)
+    }
 }
 impl fmt::Display for List {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_printable() {
+            let chars = self
+                .elements
+                .iter()
+                .filter_map(|e| e.to_u32().and_then(std::char::from_u32));
+            return syntax::write_quoted(f, chars, '"');
+        }
         r#try!(write!(f, "["));
         for (i, x) in self.elements.iter().enumerate() {
             if i != 0 {
@@ -730,6 +881,12 @@ impl fmt::Display for Map {
         Ok(())
     }
 }
+impl Map {
+    /// Returns a view of the map as an Elixir struct, if it has a `__struct__` key.
+    pub fn as_elixir_struct(&self) -> Option<elixir::Struct<'_>> {
+        elixir::Struct::new(self)
+    }
+}
 impl From<Vec<(Term, Term)>> for Map {
     fn from(entries: Vec<(Term, Term)>) -> Self {
         Map { entries: entries }
@@ -0,0 +1,209 @@
+//! Support code for the `eetf!` macro.
+use super::*;
+
+/// Builds a `Term` from an Erlang-like syntax.
+///
+/// - `{...}`, `[...]`, `[H | T]` and `#{K => V}` build tuples, lists, improper lists and maps.
+/// - Identifiers, `true` and `false` are atoms.
+/// - Integer literals are `FixInteger` if they are `i32` and `BigInteger` otherwise
+///   (e.g. `10_000_000_000i64`); negative numbers are written `-1`.
+/// - Float literals are `Float`, and character literals are integers; an out-of-range float
+///   literal such as `1e400` does not compile.
+/// - String literals are lists of characters, like in Erlang; use `<<"...">>` for binaries.
+/// - `<<...>>` builds a binary from comma-separated segments, each being a byte,
+///   a UTF-8 string literal or a parenthesized expression (e.g. a byte slice).
+/// - A parenthesized Rust expression `(expr)` is converted with `Term::from`
+///   (an `f64` has to be checked with `Term::try_from` first, e.g. `(Term::try_from(x)?)`).
+///
+/// # Examples
+///
+/// ```
+/// use eetf::{eetf, Atom, Term};
+///
+/// let name = Atom::from("alice");
+/// let term = eetf!({ok, [1, -2, 3.5], #{"k" => <<1, 2, "abc">>}, (name)});
+/// assert_eq!(
+///     term.to_string(),
+///     "{ok,[1,-2,3.5],#{\"k\"=><<1,2,97,98,99>>},alice}"
+/// );
+/// assert_eq!(eetf!([a, b | c]), "[a,b|c]".parse::<Term>().unwrap());
+/// ```
+///
+/// ```compile_fail
+/// eetf::eetf!([1e400]);
+/// ```
+#[macro_export]
+macro_rules! eetf {
+    ({ $($body:tt)* }) => {
+        $crate::eetf!(@seq tuple [] $($body)*)
+    };
+    ([ $($body:tt)* ]) => {
+        $crate::eetf!(@seq list [] $($body)*)
+    };
+    (# { $($body:tt)* }) => {
+        $crate::eetf!(@seq map [] $($body)*)
+    };
+    (<< $($body:tt)*) => {
+        $crate::eetf!(@bin [] [] $($body)*)
+    };
+    (- $value:literal) => {
+        $crate::macros::Literal::into_term(-$value)
+    };
+    ($value:literal) => {
+        $crate::macros::Literal::into_term($value)
+    };
+    ($atom:ident) => {
+        $crate::Term::from($crate::Atom::from(stringify!($atom)))
+    };
+    (($value:expr)) => {
+        $crate::Term::from($value)
+    };
+
+    // Sequences: elements are munched one by one. Elements made of several tokens
+    // are first rewritten into a parenthesized expression.
+    (@seq tuple [$($acc:tt)*]) => {
+        $crate::Term::from($crate::Tuple::from(vec![$($acc)*]))
+    };
+    (@seq list [$($acc:tt)*]) => {
+        $crate::Term::from($crate::List::from(vec![$($acc)*]))
+    };
+    (@seq map [$($acc:tt)*]) => {
+        $crate::Term::from($crate::Map::from($crate::eetf!(@pairs [] $($acc)*)))
+    };
+    (@seq list [$($acc:tt)*] | $($last:tt)+) => {
+        $crate::Term::from($crate::ImproperList::from((
+            vec![$($acc)*],
+            $crate::eetf!($($last)+),
+        )))
+    };
+    (@seq $kind:ident [$($acc:tt)*] << $($rest:tt)*) => {
+        $crate::eetf!(@bin [] [@seq $kind [$($acc)*]] $($rest)*)
+    };
+    (@seq $kind:ident [$($acc:tt)*] - $value:literal $($rest:tt)*) => {
+        $crate::eetf!(@seq $kind [$($acc)*] ($crate::eetf!(- $value)) $($rest)*)
+    };
+    (@seq $kind:ident [$($acc:tt)*] # $map:tt $($rest:tt)*) => {
+        $crate::eetf!(@seq $kind [$($acc)*] ($crate::eetf!(# $map)) $($rest)*)
+    };
+    (@seq list [$($acc:tt)*] $elem:tt | $($last:tt)+) => {
+        $crate::eetf!(@seq list [$($acc)* $crate::eetf!($elem),] | $($last)+)
+    };
+    (@seq map [$($acc:tt)*] $key:tt => $($rest:tt)*) => {
+        $crate::eetf!(@seq map [$($acc)* $crate::eetf!($key),] $($rest)*)
+    };
+    (@seq $kind:ident [$($acc:tt)*] $elem:tt $(, $($rest:tt)*)?) => {
+        $crate::eetf!(@seq $kind [$($acc)* $crate::eetf!($elem),] $($($rest)*)?)
+    };
+
+    (@pairs [$($acc:tt)*]) => {
+        vec![$($acc)*]
+    };
+    (@pairs [$($acc:tt)*] $key:expr, $value:expr, $($rest:tt)*) => {
+        $crate::eetf!(@pairs [$($acc)* ($key, $value),] $($rest)*)
+    };
+
+    // Binaries: segments are collected until `>>`, then the binary is handed back to
+    // the enclosing sequence (if any) as a parenthesized expression.
+    (@bin [$($seg:tt)*] [] >>) => {
+        $crate::eetf!(@bin_term $($seg)*)
+    };
+    (@bin [$($seg:tt)*] [$($cont:tt)+] >> $($rest:tt)*) => {
+        $crate::eetf!($($cont)+ ($crate::eetf!(@bin_term $($seg)*)) $($rest)*)
+    };
+    (@bin [$($seg:tt)*] [$($cont:tt)*] $value:tt , $($rest:tt)*) => {
+        $crate::eetf!(@bin [$($seg)* ($value)] [$($cont)*] $($rest)*)
+    };
+    (@bin [$($seg:tt)*] [$($cont:tt)*] $value:tt >> $($rest:tt)*) => {
+        $crate::eetf!(@bin [$($seg)* ($value)] [$($cont)*] >> $($rest)*)
+    };
+    (@bin_term $($seg:tt)*) => {{
+        let mut bytes = Vec::new();
+        $($crate::macros::Segment::write_to($seg, &mut bytes);)*
+        $crate::Term::from($crate::Binary::from(bytes))
+    }};
+}
+
+/// A literal which can appear in `eetf!`.
+pub trait Literal {
+    fn into_term(self) -> Term;
+}
+impl Literal for i32 {
+    fn into_term(self) -> Term {
+        Term::from(FixInteger::from(self))
+    }
+}
+impl Literal for i64 {
+    fn into_term(self) -> Term {
+        match i32::try_from(self) {
+            Ok(value) => Term::from(FixInteger::from(value)),
+            Err(_) => Term::from(BigInteger::from(self)),
+        }
+    }
+}
+impl Literal for u64 {
+    fn into_term(self) -> Term {
+        match i32::try_from(self) {
+            Ok(value) => Term::from(FixInteger::from(value)),
+            Err(_) => Term::from(BigInteger::from(self)),
+        }
+    }
+}
+/// Float literals are always finite, as out-of-range ones (e.g. `1e400`) are rejected
+/// by the compiler.
+impl Literal for f64 {
+    fn into_term(self) -> Term {
+        Term::from(Float::try_from(self).expect("non-finite float literal"))
+    }
+}
+impl Literal for bool {
+    fn into_term(self) -> Term {
+        Term::from(Atom::from(if self { "true" } else { "false" }))
+    }
+}
+impl Literal for char {
+    fn into_term(self) -> Term {
+        (self as u32 as i64).into_term()
+    }
+}
+impl Literal for &str {
+    fn into_term(self) -> Term {
+        Term::from(List::from(
+            self.chars().map(Literal::into_term).collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// A segment of a binary built by `eetf!`.
+pub trait Segment {
+    fn write_to(self, bytes: &mut Vec<u8>);
+}
+impl Segment for u8 {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.push(self);
+    }
+}
+impl Segment for &str {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.as_bytes());
+    }
+}
+impl Segment for &String {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.as_bytes());
+    }
+}
+impl Segment for &[u8] {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self);
+    }
+}
+impl Segment for &Vec<u8> {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self);
+    }
+}
+impl Segment for &Binary {
+    fn write_to(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.bytes);
+    }
+}
@@ -0,0 +1,247 @@
+//! Conversion between terms and [MessagePack](https://msgpack.org) values of
+//! [rmpv](https://docs.rs/rmpv).
+//!
+//! This module is available if the `msgpack` feature is enabled.
+//!
+//! # Mapping
+//!
+//! | MessagePack                  | Erlang                                          |
+//! |------------------------------|-------------------------------------------------|
+//! | nil                          | `null` (like the `json` and `cbor` modules)     |
+//! | boolean                      | `true` / `false`                                |
+//! | integer                      | integer                                         |
+//! | float                        | float                                           |
+//! | str, bin                     | binary (UTF-8 binaries are written as str)      |
+//! | array                        | list                                            |
+//! | map                          | map                                             |
+//! | ext `ATOM_EXT`               | other atoms (the UTF-8 name)                    |
+//! | ext `TUPLE_EXT`              | tuple (an encoded array of the elements)        |
+//! | ext `IMPROPER_LIST_EXT`      | improper list (an encoded array of the elements and the tail) |
+//! | ext `BITSTRING_EXT`          | bitstring (the number of bits in the last byte, then the bytes) |
+//! | ext `TERM_EXT`               | pids, ports, references, funs and integers out of the 64-bit range (ETF) |
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::{msgpack, Term};
+//!
+//! let term: Term = "{ok,[1,<<\"a\">>],<nonode@nohost.1.2>}".parse().unwrap();
+//! let bytes = msgpack::to_vec(&term).unwrap();
+//! assert_eq!(msgpack::from_slice(&bytes).unwrap(), term);
+//! ```
+use super::*;
+use rmpv::{Integer, Value};
+use std::error;
+
+/// The extension type of atoms other than `null`, `true` and `false`.
+pub const ATOM_EXT: i8 = 1;
+/// The extension type of tuples.
+pub const TUPLE_EXT: i8 = 2;
+/// The extension type of improper lists.
+pub const IMPROPER_LIST_EXT: i8 = 3;
+/// The extension type of bitstrings.
+pub const BITSTRING_EXT: i8 = 4;
+/// The extension type of terms encoded in the External Term Format.
+pub const TERM_EXT: i8 = 5;
+
+/// Errors which can occur when converting between terms and MessagePack
+#[derive(Debug)]
+pub enum Error {
+    Encode(Box<EncodeError>),
+    Decode(Box<DecodeError>),
+    Invalid { message: String },
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(ref x) => x.fmt(f),
+            Error::Decode(ref x) => x.fmt(f),
+            Error::Invalid { ref message } => write!(f, "Invalid MessagePack data: {}", message),
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Encode(_) => "Cannot encode the term",
+            Error::Decode(_) => "Cannot decode the term",
+            Error::Invalid { .. } => "Invalid MessagePack data",
+        }
+    }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref x) => Some(&**x),
+            Error::Decode(ref x) => Some(&**x),
+            Error::Invalid { .. } => None,
+        }
+    }
+}
+impl From<EncodeError> for Error {
+    fn from(err: EncodeError) -> Self {
+        Error::Encode(Box::new(err))
+    }
+}
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::Decode(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Converts a term into a MessagePack value.
+pub fn to_value(term: &Term) -> Result<Value> {
+    Ok(match *term {
+        Term::Atom(ref x) => match x.name.as_str() {
+            "null" => Value::Nil,
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            name => Value::Ext(ATOM_EXT, name.as_bytes().to_vec()),
+        },
+        Term::FixInteger(ref x) => Value::from(x.value),
+        Term::BigInteger(ref x) => {
+            if let Some(n) = x.value.to_i64() {
+                Value::from(n)
+            } else if let Some(n) = x.value.to_u64() {
+                Value::from(n)
+            } else {
+                term_ext(term)?
+            }
+        }
+        Term::Float(ref x) => Value::F64(x.value),
+        Term::Binary(ref x) => match str::from_utf8(&x.bytes) {
+            Ok(s) => Value::from(s),
+            Err(_) => Value::Binary(x.bytes.clone()),
+        },
+        Term::BitBinary(ref x) => {
+            let mut data = Vec::with_capacity(x.bytes.len() + 1);
+            data.push(x.tail_bits_size);
+            data.extend_from_slice(&x.bytes);
+            Value::Ext(BITSTRING_EXT, data)
+        }
+        Term::List(ref x) => Value::Array(to_values(&x.elements)?),
+        Term::ImproperList(ref x) => {
+            let mut elements = to_values(&x.elements)?;
+            elements.push(to_value(&x.last)?);
+            Value::Ext(IMPROPER_LIST_EXT, encode_value(&Value::Array(elements)))
+        }
+        Term::Tuple(ref x) => Value::Ext(
+            TUPLE_EXT,
+            encode_value(&Value::Array(to_values(&x.elements)?)),
+        ),
+        Term::Map(ref x) => Value::Map(
+            x.entries
+                .iter()
+                .map(|(k, v)| Ok((to_value(k)?, to_value(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+        Term::Pid(_)
+        | Term::Port(_)
+        | Term::Reference(_)
+        | Term::ExternalFun(_)
+        | Term::InternalFun(_) => term_ext(term)?,
+    })
+}
+
+/// Converts a MessagePack value into a term.
+pub fn from_value(value: &Value) -> Result<Term> {
+    Ok(match *value {
+        Value::Nil => Term::from(Atom::from("null")),
+        Value::Boolean(x) => Term::from(Atom::from(if x { "true" } else { "false" })),
+        Value::Integer(ref x) => integer_term(x),
+        Value::F32(x) => Term::from(float(x.into())?),
+        Value::F64(x) => Term::from(float(x)?),
+        Value::String(ref x) => Term::from(Binary::from(x.as_bytes())),
+        Value::Binary(ref x) => Term::from(Binary::from(x.as_slice())),
+        Value::Array(ref x) => Term::from(List::from(from_values(x)?)),
+        Value::Map(ref x) => Term::from(Map::from(
+            x.iter()
+                .map(|(k, v)| Ok((from_value(k)?, from_value(v)?)))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        Value::Ext(ATOM_EXT, ref data) => match str::from_utf8(data) {
+            Ok(name) => Term::from(Atom::from(name)),
+            Err(_) => return Err(invalid("atom name is not UTF-8")),
+        },
+        Value::Ext(TUPLE_EXT, ref data) => {
+            Term::from(Tuple::from(from_values(&decode_array(data)?)?))
+        }
+        Value::Ext(IMPROPER_LIST_EXT, ref data) => {
+            let mut elements = from_values(&decode_array(data)?)?;
+            match elements.pop() {
+                Some(last) if !elements.is_empty() => {
+                    Term::from(ImproperList::from((elements, last)))
+                }
+                _ => return Err(invalid("improper list has less than two elements")),
+            }
+        }
+        Value::Ext(BITSTRING_EXT, ref data) => match data.split_first() {
+            Some((&bits, bytes)) if (1..=8).contains(&bits) && !bytes.is_empty() => {
+                Term::from(BitBinary::from((bytes.to_vec(), bits)))
+            }
+            _ => return Err(invalid("malformed bitstring")),
+        },
+        Value::Ext(TERM_EXT, ref data) => Term::decode(io::Cursor::new(data))?,
+        Value::Ext(ty, _) => return Err(invalid(&format!("unknown extension type {}", ty))),
+    })
+}
+
+/// Converts a term into a MessagePack value and encodes it into a byte vector.
+pub fn to_vec(term: &Term) -> Result<Vec<u8>> {
+    to_value(term).map(|value| encode_value(&value))
+}
+
+/// Decodes a MessagePack value from `bytes` and converts it into a term.
+pub fn from_slice(bytes: &[u8]) -> Result<Term> {
+    let value = rmpv::decode::read_value(&mut &bytes[..]).map_err(|e| invalid(&e.to_string()))?;
+    from_value(&value)
+}
+
+fn to_values(terms: &[Term]) -> Result<Vec<Value>> {
+    terms.iter().map(to_value).collect()
+}
+
+fn from_values(values: &[Value]) -> Result<Vec<Term>> {
+    values.iter().map(from_value).collect()
+}
+
+fn float(x: f64) -> Result<Float> {
+    Float::try_from(x).map_err(|_| invalid("non-finite float"))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Invalid {
+        message: message.to_owned(),
+    }
+}
+
+fn term_ext(term: &Term) -> Result<Value> {
+    let mut buf = Vec::new();
+    term.encode(&mut buf)?;
+    Ok(Value::Ext(TERM_EXT, buf))
+}
+
+fn encode_value(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, value).expect("writing to a vector never fails");
+    buf
+}
+
+fn decode_array(data: &[u8]) -> Result<Vec<Value>> {
+    match rmpv::decode::read_value(&mut &data[..]) {
+        Ok(Value::Array(x)) => Ok(x),
+        Ok(_) => Err(invalid("extension payload is not an array")),
+        Err(e) => Err(invalid(&e.to_string())),
+    }
+}
+
+fn integer_term(n: &Integer) -> Term {
+    if let Some(n) = n.as_i64() {
+        match i32::try_from(n) {
+            Ok(n) => Term::from(FixInteger::from(n)),
+            Err(_) => Term::from(BigInteger::from(n)),
+        }
+    } else {
+        Term::from(BigInteger::from(n.as_u64().unwrap_or(u64::MAX)))
+    }
+}
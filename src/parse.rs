@@ -0,0 +1,839 @@
+use super::*;
+use num::bigint::BigInt;
+use num::bigint::BigUint;
+use num::traits::One;
+use num::traits::ToPrimitive;
+use std::error;
+use std::fmt;
+use std::str;
+
+/// Errors which can occur when parsing a term from its textual representation
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    UnexpectedEof,
+    UnexpectedChar { position: usize, found: char },
+    InvalidNumber { position: usize },
+    InvalidEscape { position: usize },
+    InvalidSegment { position: usize },
+    UnsupportedTerm { position: usize, kind: String },
+    TooDeep { position: usize },
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ParseError::*;
+        match *self {
+            UnexpectedEof => write!(f, "Unexpected end of input"),
+            UnexpectedChar { position, found } => {
+                write!(
+                    f,
+                    "Unexpected character {:?} at position {}",
+                    found, position
+                )
+            }
+            InvalidNumber { position } => write!(f, "Invalid number at position {}", position),
+            InvalidEscape { position } => {
+                write!(f, "Invalid escape sequence at position {}", position)
+            }
+            InvalidSegment { position } => {
+                write!(f, "Invalid binary segment at position {}", position)
+            }
+            UnsupportedTerm { position, ref kind } => write!(
+                f,
+                "{} cannot be constructed from text (position {})",
+                kind, position
+            ),
+            TooDeep { position } => write!(
+                f,
+                "Term nested deeper than {} levels at position {}",
+                MAX_DEPTH, position
+            ),
+        }
+    }
+}
+impl error::Error for ParseError {
+    fn description(&self) -> &str {
+        use self::ParseError::*;
+        match *self {
+            UnexpectedEof => "Unexpected end of input",
+            UnexpectedChar { .. } => "Unexpected character",
+            InvalidNumber { .. } => "Invalid number",
+            InvalidEscape { .. } => "Invalid escape sequence",
+            InvalidSegment { .. } => "Invalid binary segment",
+            UnsupportedTerm { .. } => "Unsupported term",
+            TooDeep { .. } => "Term nested too deeply",
+        }
+    }
+}
+
+pub type ParseResult = Result<Term, ParseError>;
+
+/// The maximum nesting depth of terms, which bounds the recursion of the parser.
+const MAX_DEPTH: usize = 128;
+
+/// Parser for the Erlang literal syntax.
+///
+/// Accepts everything `impl fmt::Display for Term` produces, except internal funs
+/// (`#Fun<...>`) which do not carry enough information to be rebuilt.
+pub struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+    /// Parses a term which starts at byte `pos` of `input`, returning it and the position
+    /// just after it.
+    pub fn parse_term_at(input: &'a str, pos: usize) -> Result<(Term, usize), ParseError> {
+        let mut parser = Parser {
+            input,
+            pos,
+            depth: 0,
+        };
+        let term = parser.parse_term()?;
+        Ok((term, parser.pos))
+    }
+    pub fn parse(mut self) -> ParseResult {
+        let term = self.parse_term()?;
+        self.skip_whitespace();
+        // A trailing dot is accepted so that terms can be pasted from an Erlang shell.
+        if self.eat('.') {
+            self.skip_whitespace();
+        }
+        match self.peek() {
+            None => Ok(term),
+            Some(c) => Err(self.unexpected(c)),
+        }
+    }
+    fn parse_term(&mut self) -> ParseResult {
+        self.nested(Self::parse_value)
+    }
+    fn nested<T>(
+        &mut self,
+        parse: fn(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError::TooDeep { position: self.pos });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+    fn parse_value(&mut self) -> ParseResult {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(ParseError::UnexpectedEof),
+            Some('{') => self.parse_tuple(),
+            Some('[') => self.parse_list(),
+            Some('<') if self.rest().starts_with("<<") => self.parse_binary(),
+            Some('<') => self.parse_pid(),
+            Some('#') => self.parse_hash(),
+            Some('"') => self.parse_string(),
+            Some('\'') => self.parse_quoted_atom().map(Term::from),
+            Some(c) if c == '$' || c == '-' || c == '+' || c.is_ascii_digit() => {
+                self.parse_number()
+            }
+            Some(c) if syntax::is_atom_start(c) => {
+                let atom = self.parse_unquoted_atom();
+                if atom.name == "fun" {
+                    self.parse_external_fun()
+                } else {
+                    Ok(Term::from(atom))
+                }
+            }
+            Some(c) => Err(self.unexpected(c)),
+        }
+    }
+    fn parse_tuple(&mut self) -> ParseResult {
+        self.expect('{')?;
+        let elements = self.parse_elements('}')?;
+        Ok(Term::from(Tuple::from(elements)))
+    }
+    fn parse_list(&mut self) -> ParseResult {
+        self.expect('[')?;
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Term::from(List::nil()));
+        }
+        let mut elements = Vec::new();
+        loop {
+            elements.push(self.parse_term()?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Term::from(List::from(elements))),
+                Some('|') => break,
+                Some(c) => return Err(self.unexpected_at(self.pos - c.len_utf8(), c)),
+                None => return Err(ParseError::UnexpectedEof),
+            }
+        }
+        let last = self.parse_term()?;
+        self.skip_whitespace();
+        self.expect(']')?;
+        match last {
+            Term::List(tail) => {
+                elements.extend(tail.elements);
+                Ok(Term::from(List::from(elements)))
+            }
+            Term::ImproperList(tail) => {
+                elements.extend(tail.elements);
+                Ok(Term::from(ImproperList::from((elements, *tail.last))))
+            }
+            last => Ok(Term::from(ImproperList::from((elements, last)))),
+        }
+    }
+    fn parse_hash(&mut self) -> ParseResult {
+        let position = self.pos;
+        self.expect('#')?;
+        if self.peek() == Some('{') {
+            return self.parse_map();
+        }
+        let name = self.take_while(|c| c.is_ascii_alphabetic());
+        match name {
+            "Port" => self.parse_port(),
+            "Ref" => self.parse_reference(),
+            "Fun" => Err(ParseError::UnsupportedTerm {
+                position,
+                kind: "InternalFun".to_string(),
+            }),
+            _ => match self.peek() {
+                Some(c) => Err(self.unexpected(c)),
+                None => Err(ParseError::UnexpectedEof),
+            },
+        }
+    }
+    fn parse_map(&mut self) -> ParseResult {
+        self.expect('{')?;
+        self.skip_whitespace();
+        let mut entries = Vec::new();
+        if self.eat('}') {
+            return Ok(Term::from(Map::from(entries)));
+        }
+        loop {
+            let k = self.parse_term()?;
+            self.skip_whitespace();
+            self.expect_str("=>")?;
+            let v = self.parse_term()?;
+            entries.push((k, v));
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(Term::from(Map::from(entries))),
+                Some(c) => return Err(self.unexpected_at(self.pos - c.len_utf8(), c)),
+                None => return Err(ParseError::UnexpectedEof),
+            }
+        }
+    }
+    fn parse_pid(&mut self) -> ParseResult {
+        self.expect('<')?;
+        let node = self.parse_atom()?;
+        self.expect('.')?;
+        let id = self.parse_u32()?;
+        self.expect('.')?;
+        let serial = self.parse_u32()?;
+        self.expect('>')?;
+        Ok(Term::from(Pid::new(node, id, serial, 0)))
+    }
+    fn parse_port(&mut self) -> ParseResult {
+        self.expect('<')?;
+        let node = self.parse_atom()?;
+        self.expect('.')?;
+        let id = self.parse_u32()?;
+        self.expect('>')?;
+        Ok(Term::from(Port {
+            node,
+            id,
+            creation: 0,
+        }))
+    }
+    fn parse_reference(&mut self) -> ParseResult {
+        self.expect('<')?;
+        let node = self.parse_atom()?;
+        let mut id = Vec::new();
+        while self.eat('.') {
+            id.push(self.parse_u32()?);
+        }
+        self.expect('>')?;
+        Ok(Term::from(Reference {
+            node,
+            id,
+            creation: 0,
+        }))
+    }
+    fn parse_external_fun(&mut self) -> ParseResult {
+        self.skip_whitespace();
+        let module = self.parse_atom()?;
+        self.expect(':')?;
+        let function = self.parse_atom()?;
+        self.expect('/')?;
+        let position = self.pos;
+        let arity = self.parse_u32()?;
+        if arity > 0xFF {
+            return Err(ParseError::InvalidNumber { position });
+        }
+        Ok(Term::from(ExternalFun {
+            module,
+            function,
+            arity: arity as u8,
+        }))
+    }
+    fn parse_string(&mut self) -> ParseResult {
+        let mut elements = Vec::new();
+        // Adjacent string literals are concatenated, as in Erlang source code.
+        while self.peek() == Some('"') {
+            for c in self.parse_quoted('"')?.chars() {
+                elements.push(Term::from(FixInteger::from(c as i32)));
+            }
+            self.skip_whitespace();
+        }
+        Ok(Term::from(List::from(elements)))
+    }
+    fn parse_binary(&mut self) -> ParseResult {
+        self.expect_str("<<")?;
+        let mut writer = BitWriter::new();
+        self.skip_whitespace();
+        if self.eat_str(">>") {
+            return Ok(writer.finish());
+        }
+        loop {
+            self.parse_segment(&mut writer)?;
+            self.skip_whitespace();
+            if self.eat_str(">>") {
+                return Ok(writer.finish());
+            }
+            self.expect(',')?;
+        }
+    }
+    fn parse_segment(&mut self, writer: &mut BitWriter) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        let position = self.pos;
+        let value = match self.peek() {
+            Some('"') => Segment::Chars(self.parse_quoted('"')?),
+            Some('<') if self.rest().starts_with("<<") => {
+                Segment::Bits(self.nested(Self::parse_binary)?)
+            }
+            Some(_) => match self.parse_number()? {
+                Term::Float(x) => Segment::Float(x.value),
+                Term::FixInteger(x) => Segment::Integer(BigInt::from(x.value)),
+                Term::BigInteger(x) => Segment::Integer(x.value),
+                _ => unreachable!(),
+            },
+            None => return Err(ParseError::UnexpectedEof),
+        };
+        let size = if self.eat(':') {
+            Some(self.parse_u32()? as usize)
+        } else {
+            None
+        };
+        let mut spec = SegmentSpec::default();
+        if self.eat('/') {
+            loop {
+                let position = self.pos;
+                match self.take_while(|c| c.is_ascii_alphanumeric()) {
+                    "integer" => spec.kind = SegmentKind::Integer,
+                    "float" => spec.kind = SegmentKind::Float,
+                    "binary" | "bytes" => {
+                        spec.kind = SegmentKind::Binary;
+                        spec.unit = 8;
+                    }
+                    "bitstring" | "bits" => {
+                        spec.kind = SegmentKind::Binary;
+                        spec.unit = 1;
+                    }
+                    "utf8" => spec.kind = SegmentKind::Utf8,
+                    "big" => spec.little = false,
+                    "little" => spec.little = true,
+                    "signed" | "unsigned" => {}
+                    _ => return Err(ParseError::InvalidSegment { position }),
+                }
+                if !self.eat('-') {
+                    break;
+                }
+            }
+        }
+        let invalid = ParseError::InvalidSegment { position };
+        let unit = match (&value, spec.kind) {
+            (_, SegmentKind::Binary) | (Segment::Bits(_), SegmentKind::Default) => spec.unit,
+            _ => 1,
+        };
+        let size = match size {
+            Some(n) if n.saturating_mul(unit) > MAX_SEGMENT_BITS => return Err(invalid),
+            size => size.map(|n| n * unit),
+        };
+        match (value, spec.kind) {
+            (Segment::Chars(s), SegmentKind::Utf8) => {
+                for b in s.as_bytes() {
+                    writer.push_uint(&BigUint::from(*b), 8, false);
+                }
+            }
+            (Segment::Chars(s), SegmentKind::Binary) => {
+                let bytes = s
+                    .chars()
+                    .map(|c| u8::try_from(c).map_err(|_| invalid.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let bits = size.unwrap_or(bytes.len() * 8);
+                if bits > bytes.len() * 8 {
+                    return Err(invalid);
+                }
+                writer.push_bits(&bytes, bits);
+            }
+            (Segment::Chars(s), kind) => {
+                for c in s.chars() {
+                    writer
+                        .push_segment(
+                            &Segment::Integer(BigInt::from(c as u32)),
+                            size,
+                            kind,
+                            spec.little,
+                        )
+                        .ok_or_else(|| invalid.clone())?;
+                }
+            }
+            (value, kind) => writer
+                .push_segment(&value, size, kind, spec.little)
+                .ok_or(invalid)?,
+        }
+        Ok(())
+    }
+    fn parse_number(&mut self) -> ParseResult {
+        let position = self.pos;
+        let negative = if self.eat('-') {
+            true
+        } else {
+            self.eat('+');
+            false
+        };
+        let mut value = if self.eat('$') {
+            BigInt::from(self.parse_char()? as u32)
+        } else {
+            let digits = self.take_digits(10);
+            if digits.is_empty() {
+                return Err(ParseError::InvalidNumber { position });
+            }
+            if self.eat('#') {
+                let radix = digits
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|r| 2 <= *r && *r <= 36)
+                    .ok_or(ParseError::InvalidNumber { position })?;
+                let digits = self.take_digits(radix);
+                BigInt::parse_bytes(digits.as_bytes(), radix)
+                    .ok_or(ParseError::InvalidNumber { position })?
+            } else if self.peek() == Some('.')
+                && self.peek_nth(1).is_some_and(|c| c.is_ascii_digit())
+            {
+                return self.parse_float_after(position, negative, &digits);
+            } else {
+                BigInt::parse_bytes(digits.as_bytes(), 10)
+                    .ok_or(ParseError::InvalidNumber { position })?
+            }
+        };
+        if negative {
+            value = -value;
+        }
+        Ok(integer_to_term(value))
+    }
+    fn parse_float_after(
+        &mut self,
+        position: usize,
+        negative: bool,
+        integer_part: &str,
+    ) -> ParseResult {
+        self.expect('.')?;
+        let mut text = String::new();
+        if negative {
+            text.push('-');
+        }
+        text.push_str(integer_part);
+        text.push('.');
+        text.push_str(&self.take_digits(10));
+        if let Some(e) = self.peek().filter(|&c| c == 'e' || c == 'E') {
+            self.bump();
+            text.push(e);
+            if let Some(sign) = self.peek().filter(|&c| c == '-' || c == '+') {
+                self.bump();
+                text.push(sign);
+            }
+            let exponent = self.take_digits(10);
+            if exponent.is_empty() {
+                return Err(ParseError::InvalidNumber { position });
+            }
+            text.push_str(&exponent);
+        }
+        text.parse::<f64>()
+            .ok()
+            .and_then(|f| Float::try_from(f).ok())
+            .map(Term::from)
+            .ok_or(ParseError::InvalidNumber { position })
+    }
+    fn parse_u32(&mut self) -> Result<u32, ParseError> {
+        let position = self.pos;
+        self.take_while(|c| c.is_ascii_digit())
+            .parse()
+            .map_err(|_| ParseError::InvalidNumber { position })
+    }
+    fn parse_atom(&mut self) -> Result<Atom, ParseError> {
+        match self.peek() {
+            Some('\'') => self.parse_quoted_atom(),
+            Some(c) if syntax::is_atom_start(c) => Ok(self.parse_unquoted_atom()),
+            Some(c) => Err(self.unexpected(c)),
+            None => Err(ParseError::UnexpectedEof),
+        }
+    }
+    fn parse_quoted_atom(&mut self) -> Result<Atom, ParseError> {
+        self.parse_quoted('\'').map(Atom::from)
+    }
+    fn parse_unquoted_atom(&mut self) -> Atom {
+        Atom::from(self.take_while(syntax::is_atom_char))
+    }
+    fn parse_quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        self.expect(quote)?;
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(ParseError::UnexpectedEof),
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') => s.push(self.parse_escape()?),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+    fn parse_char(&mut self) -> Result<char, ParseError> {
+        match self.bump() {
+            None => Err(ParseError::UnexpectedEof),
+            Some('\\') => self.parse_escape(),
+            Some(c) => Ok(c),
+        }
+    }
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        let position = self.pos - 1;
+        let invalid = ParseError::InvalidEscape { position };
+        let code = match self.bump().ok_or(ParseError::UnexpectedEof)? {
+            'b' => 8,
+            'd' => 127,
+            'e' => 27,
+            'f' => 12,
+            'n' => 10,
+            'r' => 13,
+            's' => 32,
+            't' => 9,
+            'v' => 11,
+            c @ '0'..='7' => {
+                let mut code = c.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match self.peek().and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            self.bump();
+                            code = code * 8 + d;
+                        }
+                        None => break,
+                    }
+                }
+                code
+            }
+            'x' => {
+                let digits = if self.eat('{') {
+                    let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                    self.expect('}')?;
+                    digits
+                } else {
+                    let start = self.pos;
+                    for _ in 0..2 {
+                        if self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                            self.bump();
+                        }
+                    }
+                    &self.input[start..self.pos]
+                };
+                u32::from_str_radix(digits, 16).map_err(|_| invalid.clone())?
+            }
+            '^' => match self.bump() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    c.to_ascii_lowercase() as u32 - 'a' as u32 + 1
+                }
+                Some(_) => return Err(invalid),
+                None => return Err(ParseError::UnexpectedEof),
+            },
+            c => c as u32,
+        };
+        std::char::from_u32(code).ok_or(invalid)
+    }
+    fn parse_elements(&mut self, close: char) -> Result<Vec<Term>, ParseError> {
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.eat(close) {
+            return Ok(elements);
+        }
+        loop {
+            elements.push(self.parse_term()?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => {}
+                Some(c) if c == close => return Ok(elements),
+                Some(c) => return Err(self.unexpected_at(self.pos - c.len_utf8(), c)),
+                None => return Err(ParseError::UnexpectedEof),
+            }
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(found) => Err(self.unexpected(found)),
+                None => Err(ParseError::UnexpectedEof),
+            }
+        }
+    }
+    fn expect_str(&mut self, s: &str) -> Result<(), ParseError> {
+        for c in s.chars() {
+            self.expect(c)?;
+        }
+        Ok(())
+    }
+    fn take_while<F>(&mut self, f: F) -> &'a str
+    where
+        F: Fn(char) -> bool,
+    {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+    fn take_digits(&mut self, radix: u32) -> String {
+        let digits = self.take_while(|c| c.is_digit(radix) || c == '_');
+        digits.chars().filter(|&c| c != '_').collect()
+    }
+    fn skip_whitespace(&mut self) {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek() != Some('%') {
+                return;
+            }
+            self.take_while(|c| c != '\n');
+        }
+    }
+    fn unexpected(&self, found: char) -> ParseError {
+        self.unexpected_at(self.pos, found)
+    }
+    fn unexpected_at(&self, position: usize, found: char) -> ParseError {
+        ParseError::UnexpectedChar { position, found }
+    }
+}
+
+fn integer_to_term(value: BigInt) -> Term {
+    match value.to_i32() {
+        Some(value) => Term::from(FixInteger::from(value)),
+        None => Term::from(BigInteger { value }),
+    }
+}
+
+enum Segment {
+    Integer(BigInt),
+    Float(f64),
+    Chars(String),
+    Bits(Term),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SegmentKind {
+    Default,
+    Integer,
+    Float,
+    Binary,
+    Utf8,
+}
+
+/// The maximum size of a segment in bits, which keeps a literal like
+/// `<<1:4000000000>>` from allocating without bound.
+const MAX_SEGMENT_BITS: usize = 1 << 24;
+
+struct SegmentSpec {
+    kind: SegmentKind,
+    little: bool,
+
+    /// The number of bits in a unit of the size of binary segments.
+    unit: usize,
+}
+impl Default for SegmentSpec {
+    fn default() -> Self {
+        SegmentSpec {
+            kind: SegmentKind::Default,
+            little: false,
+            unit: 8,
+        }
+    }
+}
+
+/// Accumulates the bits of a binary literal, most significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            bits: 0,
+        }
+    }
+    fn push_bit(&mut self, bit: bool) {
+        if self.bits % 8 == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+        }
+        self.bits += 1;
+    }
+    /// Pushes the first `bits` bits of `bytes`.
+    fn push_bits(&mut self, bytes: &[u8], bits: usize) {
+        for i in 0..bits {
+            self.push_bit(bytes[i / 8] & (0x80 >> (i % 8)) != 0);
+        }
+    }
+    /// Pushes the lowest `bits` bits of `value`.
+    ///
+    /// In little-endian, the whole bytes are pushed from the least significant one,
+    /// followed by the remaining most significant bits, as in Erlang where
+    /// `<<1:12/little>>` is `<<1,0:4>>`.
+    fn push_uint(&mut self, value: &BigUint, bits: usize, little: bool) {
+        let len = bits.div_ceil(8);
+        let mut bytes = value.to_bytes_le();
+        bytes.resize(len, 0);
+        let tail = bits % 8;
+        if little {
+            for &b in &bytes[..bits / 8] {
+                self.push_uint_bits(b, 8);
+            }
+            if tail != 0 {
+                self.push_uint_bits(bytes[len - 1], tail);
+            }
+        } else {
+            let mut bytes = bytes.iter().rev();
+            if tail != 0 {
+                self.push_uint_bits(*bytes.next().unwrap(), tail);
+            }
+            for &b in bytes {
+                self.push_uint_bits(b, 8);
+            }
+        }
+    }
+    /// Pushes the lowest `bits` bits of `byte`.
+    fn push_uint_bits(&mut self, byte: u8, bits: usize) {
+        for i in (0..bits).rev() {
+            self.push_bit(byte & (1 << i) != 0);
+        }
+    }
+    fn push_segment(
+        &mut self,
+        value: &Segment,
+        size: Option<usize>,
+        kind: SegmentKind,
+        little: bool,
+    ) -> Option<()> {
+        match (value, kind) {
+            (Segment::Integer(n), SegmentKind::Default)
+            | (Segment::Integer(n), SegmentKind::Integer) => {
+                let bits = size.unwrap_or(8);
+                let modulus = BigInt::one() << bits;
+                let n = ((n % &modulus) + &modulus) % &modulus;
+                self.push_uint(&n.to_biguint()?, bits, little);
+            }
+            (Segment::Integer(n), SegmentKind::Utf8) if size.is_none() => {
+                let c = std::char::from_u32(n.to_u32()?)?;
+                let mut buf = [0; 4];
+                self.push_bits(c.encode_utf8(&mut buf).as_bytes(), c.len_utf8() * 8);
+            }
+            (Segment::Integer(n), SegmentKind::Float) => {
+                self.push_float(n.to_f64()?, size, little)?;
+            }
+            (Segment::Float(f), SegmentKind::Default) | (Segment::Float(f), SegmentKind::Float) => {
+                self.push_float(*f, size, little)?;
+            }
+            (Segment::Bits(t), SegmentKind::Default) | (Segment::Bits(t), SegmentKind::Binary) => {
+                let (bytes, total) = match *t {
+                    Term::Binary(ref b) => (b.bytes.clone(), b.bytes.len() * 8),
+                    Term::BitBinary(ref b) => {
+                        let mut bytes = b.bytes.clone();
+                        let tail = b.tail_bits_size as usize;
+                        if let Some(last) = bytes.last_mut().filter(|_| tail != 0) {
+                            *last <<= 8 - tail;
+                        }
+                        let total = bytes.len().saturating_sub(1) * 8 + tail;
+                        (bytes, total)
+                    }
+                    _ => unreachable!(),
+                };
+                let bits = size.unwrap_or(total);
+                if bits > total {
+                    return None;
+                }
+                self.push_bits(&bytes, bits);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+    fn push_float(&mut self, f: f64, size: Option<usize>, little: bool) -> Option<()> {
+        let mut bytes = match size.unwrap_or(64) {
+            64 => f.to_bits().to_be_bytes().to_vec(),
+            32 => (f as f32).to_bits().to_be_bytes().to_vec(),
+            _ => return None,
+        };
+        if little {
+            bytes.reverse();
+        }
+        let bits = bytes.len() * 8;
+        self.push_bits(&bytes, bits);
+        Some(())
+    }
+    fn finish(self) -> Term {
+        let tail = self.bits % 8;
+        if tail == 0 {
+            Term::from(Binary::from(self.bytes))
+        } else {
+            let mut bytes = self.bytes;
+            *bytes.last_mut().unwrap() >>= 8 - tail;
+            Term::from(BitBinary::from((bytes, tail as u8)))
+        }
+    }
+}
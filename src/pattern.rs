@@ -1,11 +1,14 @@
 use super::*;
 use crate::convert::AsOption;
 use crate::convert::TryAsRef;
+use crate::proplist::Key;
+use crate::spec::{MapEntrySpec, Spec};
 use num;
 use num::bigint::ToBigInt;
 use num::bigint::ToBigUint;
 use num::traits::ToPrimitive;
 use std;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 pub type Result<'a, T> = std::result::Result<T, Unmatch<'a>>;
@@ -18,12 +21,41 @@ pub trait Pattern<'a>: Debug + Clone {
     where
         Self: 'static,
     {
-        Unmatch {
-            input: input,
-            pattern: Box::new(self.clone()),
-            cause: None,
+        Unmatch::new(input, self.clone())
+    }
+
+    /// Adds a guard which the output must satisfy.
+    ///
+    /// The guard is shown by its type name in `Unmatch`, or by the name given with
+    /// [`Where::named`].
+    fn where_<F>(self, guard: F) -> Where<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::Output) -> bool,
+    {
+        Where {
+            pattern: self,
+            guard,
+            name: std::any::type_name::<F>(),
         }
     }
+
+    /// Transforms the output with `f`.
+    fn map<F, T>(self, f: F) -> Mapped<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output) -> T,
+    {
+        Mapped { pattern: self, f }
+    }
+
+    /// Describes the terms which the pattern matches, e.g. as an Erlang type.
+    ///
+    /// Conditions which cannot be described, such as guards, are left out, and the
+    /// default is [`Spec::Any`].
+    fn spec(&self) -> Spec {
+        Spec::Any
+    }
 }
 
 #[derive(Debug)]
@@ -31,20 +63,42 @@ pub struct Unmatch<'a> {
     pub input: &'a Term,
     pub pattern: Box<Debug>,
     pub cause: Option<Box<Unmatch<'a>>>,
+
+    alternatives: Vec<Unmatch<'a>>,
 }
 impl<'a> Unmatch<'a> {
+    pub fn new<P: Debug + 'static>(input: &'a Term, pattern: P) -> Self {
+        Unmatch {
+            input: input,
+            pattern: Box::new(pattern),
+            cause: None,
+            alternatives: Vec::new(),
+        }
+    }
     pub fn cause(mut self, cause: Unmatch<'a>) -> Self {
         self.cause = Some(Box::new(cause));
         self
     }
+    pub fn alternatives(mut self, alternatives: Vec<Unmatch<'a>>) -> Self {
+        self.alternatives = alternatives;
+        self
+    }
+
+    /// Returns the failures of the alternatives of an `Or`-like pattern, in the order tried.
+    pub fn tried_alternatives(&self) -> &[Unmatch<'a>] {
+        &self.alternatives
+    }
+
     pub fn depth(&self) -> usize {
-        let mut depth = 0;
-        let mut curr = &self.cause;
-        while let Some(ref next) = *curr {
-            depth += 1;
-            curr = &next.cause;
+        match self.cause {
+            Some(ref cause) => 1 + cause.depth(),
+            None => self
+                .alternatives
+                .iter()
+                .map(|x| 1 + x.depth())
+                .max()
+                .unwrap_or(0),
         }
-        depth
     }
     pub fn max_depth(self, other: Self) -> Self {
         if self.depth() < other.depth() {
@@ -53,13 +107,590 @@ impl<'a> Unmatch<'a> {
             self
         }
     }
+
+    /// Returns the path from the input to the innermost failure, following the deepest
+    /// alternative of `Or`-like patterns.
+    pub fn path(&self) -> Path<'a> {
+        let mut path = Path(Vec::new());
+        let mut curr = self;
+        while let Some(next) = curr.next() {
+            path.0.extend(step(curr.input, next.input));
+            curr = next;
+        }
+        path
+    }
+
+    /// Converts into an [`OwnedUnmatch`], which does not borrow the input.
+    pub fn into_owned(self) -> OwnedUnmatch {
+        OwnedUnmatch::from(self)
+    }
+
+    fn next(&self) -> Option<&Unmatch<'a>> {
+        self.cause
+            .as_deref()
+            .or_else(|| deepest(&self.alternatives))
+    }
+
+    /// Returns a human-readable report of the failure.
+    ///
+    /// ```
+    /// use eetf::Term;
+    /// use eetf::pattern::U8;
+    ///
+    /// let term: Term = "{ok,[1,300]}".parse().unwrap();
+    /// let e = term.as_match(("ok", (U8, U8))).unwrap_err();
+    /// assert_eq!("at $[1]: expected (U8, U8), found [1,300]", e.report().to_string());
+    /// ```
+    pub fn report(&self) -> Report<'_, 'a> {
+        Report {
+            unmatch: self,
+            max_len: 64,
+        }
+    }
+}
+impl<'a> fmt::Display for Unmatch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.report(), f)
+    }
+}
+impl<'a> std::error::Error for Unmatch<'a> {
+    fn description(&self) -> &str {
+        "The term does not match the pattern"
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum Union2<A, B> {
-    A(A),
-    B(B),
+fn deepest<'u, 'a>(alternatives: &'u [Unmatch<'a>]) -> Option<&'u Unmatch<'a>> {
+    alternatives
+        .iter()
+        .fold(None, |deepest: Option<&Unmatch>, x| match deepest {
+            Some(d) if x.depth() < d.depth() => Some(d),
+            _ => Some(x),
+        })
+}
+
+fn step<'a>(parent: &'a Term, child: &Term) -> Option<Step<'a>> {
+    let position = |terms: &[Term]| terms.iter().position(|x| std::ptr::eq(x, child));
+    match *parent {
+        Term::Tuple(ref x) => position(&x.elements).map(Step::Index),
+        Term::List(ref x) => position(&x.elements).map(Step::Index),
+        Term::ImproperList(ref x) => position(&x.elements).map(Step::Index).or_else(|| {
+            std::ptr::eq(&*x.last, child)
+                .as_option()
+                .map(|_| Step::Tail)
+        }),
+        Term::Map(ref x) => x
+            .entries
+            .iter()
+            .find(|(_, v)| std::ptr::eq(v, child))
+            .map(|(k, _)| Step::Key(k)),
+        _ => None,
+    }
+}
+
+/// A step from a term to one of its sub-terms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step<'a> {
+    /// An element of a tuple or a list.
+    Index(usize),
+
+    /// The value of a map entry with the key.
+    Key(&'a Term),
+
+    /// The tail of an improper list.
+    Tail,
+}
+
+/// The path from a term to one of its sub-terms, displayed like `$[1]#{name}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<'a>(pub Vec<Step<'a>>);
+impl<'a> fmt::Display for Path<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+        for step in &self.0 {
+            match *step {
+                Step::Index(i) => write!(f, "[{}]", i)?,
+                Step::Key(k) => write!(f, "#{{{}}}", k)?,
+                Step::Tail => write!(f, "[tail]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A human-readable report of an [`Unmatch`].
+///
+/// It shows the path to the innermost failure, the pattern expected there and the actual
+/// value, or all the alternatives tried by an `Or`-like pattern.
+#[derive(Debug)]
+pub struct Report<'u, 'a> {
+    unmatch: &'u Unmatch<'a>,
+    max_len: usize,
+}
+impl<'u, 'a> Report<'u, 'a> {
+    /// Sets the number of characters of the values shown before they are truncated
+    /// (the default is 64).
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    fn write(
+        &self,
+        f: &mut fmt::Formatter,
+        unmatch: &Unmatch<'a>,
+        mut path: Path<'a>,
+        indent: usize,
+    ) -> fmt::Result {
+        let mut curr = unmatch;
+        while let Some(ref next) = curr.cause {
+            path.0.extend(step(curr.input, next.input));
+            curr = next;
+        }
+        let mut value = curr.input.to_string();
+        if let Some((i, _)) = value.char_indices().nth(self.max_len) {
+            value.truncate(i);
+            value.push_str("...");
+        }
+        if curr.alternatives.is_empty() {
+            return write!(
+                f,
+                "at {}: expected {:?}, found {}",
+                path, curr.pattern, value
+            );
+        }
+        write!(f, "at {}: no alternative matched {}", path, value)?;
+        for x in &curr.alternatives {
+            write!(f, "\n{:indent$}- ", "", indent = indent + 2)?;
+            self.write(f, x, path.clone(), indent + 2)?;
+        }
+        Ok(())
+    }
+}
+impl<'u, 'a> fmt::Display for Report<'u, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, self.unmatch, Path(Vec::new()), 0)
+    }
+}
+
+/// An [`Unmatch`] which owns its data, e.g. to be sent across threads.
+///
+/// It describes the innermost failure of the original `Unmatch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedUnmatch {
+    /// The innermost sub-term which did not match.
+    pub input: Box<Term>,
+
+    /// The pattern which did not match `input`, formatted by `Debug`.
+    pub pattern: String,
+
+    /// The path to `input` (see [`Unmatch::path`]).
+    pub path: String,
+
+    /// The depth of the original `Unmatch`.
+    pub depth: usize,
+
+    /// The report of the original `Unmatch` (see [`Unmatch::report`]).
+    pub report: String,
+}
+impl<'a> From<Unmatch<'a>> for OwnedUnmatch {
+    fn from(unmatch: Unmatch<'a>) -> Self {
+        let mut innermost = &unmatch;
+        while let Some(next) = innermost.next() {
+            innermost = next;
+        }
+        OwnedUnmatch {
+            input: Box::new(innermost.input.clone()),
+            pattern: format!("{:?}", innermost.pattern),
+            path: unmatch.path().to_string(),
+            depth: unmatch.depth(),
+            report: unmatch.report().to_string(),
+        }
+    }
+}
+impl fmt::Display for OwnedUnmatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.report, f)
+    }
+}
+impl std::error::Error for OwnedUnmatch {
+    fn description(&self) -> &str {
+        "The term does not match the pattern"
+    }
+}
+
+/// Converts the output of a pattern into a value which does not borrow the input.
+///
+/// Borrowed outputs are converted by cloning (e.g. `&str` into `String` and `&Term`
+/// into `Term`), and tuples, vectors, options and unions are converted element-wise.
+/// [`Term::into_match`] moves the borrowed sub-terms out of the matched term instead
+/// (see [`IntoOwned::defer`]).
+pub trait IntoOwned {
+    type Owned: 'static;
+    fn into_owned(self) -> Self::Owned;
+
+    /// Marks the sub-terms needed for the owned value in `marks`, and returns a
+    /// function which makes the value from the sub-terms once they are moved out of
+    /// the matched term.
+    ///
+    /// The default converts the value by `into_owned` in advance, which suits outputs
+    /// which borrow no sub-terms.
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned>
+    where
+        Self: Sized,
+    {
+        let _ = marks;
+        let owned = self.into_owned();
+        Box::new(move |_| owned)
+    }
+}
+
+/// A function which makes an owned output from the sub-terms moved out of a term
+/// (see [`IntoOwned::defer`]).
+pub type Deferred<T> = Box<dyn FnOnce(&mut Taken) -> T>;
+
+/// The sub-terms of a term to be moved out of it by [`Term::into_match`].
+#[derive(Debug)]
+pub struct Marks {
+    addresses: HashSet<usize>,
+    counts: HashMap<usize, usize>,
+}
+impl Marks {
+    /// Makes empty marks on the sub-terms of `term`.
+    pub fn new(term: &Term) -> Self {
+        fn collect(term: &Term, addresses: &mut HashSet<usize>) {
+            addresses.extend(keys(term));
+            for child in children(term) {
+                collect(child, addresses);
+            }
+        }
+        let mut addresses = HashSet::new();
+        collect(term, &mut addresses);
+        Marks {
+            addresses,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Marks `x`, which is a sub-term of the term or the content of one, and returns the
+    /// key to take it from [`Taken`], or `None` if `x` is not a part of the term.
+    fn mark<T>(&mut self, x: &T) -> Option<usize> {
+        let key = x as *const T as usize;
+        if !self.addresses.contains(&key) {
+            return None;
+        }
+        *self.counts.entry(key).or_insert(0) += 1;
+        Some(key)
+    }
+
+    /// Moves the marked sub-terms out of `term`, leaving `[]` in their places.
+    ///
+    /// Marked sub-terms within other marked sub-terms, and those marked more than once,
+    /// are cloned.
+    pub fn take_from(&self, term: &mut Term) -> Taken {
+        let mut taken = Taken {
+            terms: HashMap::new(),
+        };
+        self.take(term, &mut taken);
+        taken
+    }
+
+    fn take(&self, term: &mut Term, taken: &mut Taken) {
+        let mut slots = keys(term)
+            .into_iter()
+            .flat_map(|key| std::iter::repeat(key).take(self.count(key)))
+            .collect::<Vec<_>>();
+        let last = match slots.pop() {
+            None => {
+                for child in children_mut(term) {
+                    self.take(child, taken);
+                }
+                return;
+            }
+            Some(last) => last,
+        };
+        for child in children(term) {
+            self.clone_marked(child, taken);
+        }
+        let term = std::mem::replace(term, Term::from(List::nil()));
+        for key in slots {
+            taken.push(key, term.clone());
+        }
+        taken.push(last, term);
+    }
+
+    fn clone_marked(&self, term: &Term, taken: &mut Taken) {
+        for key in keys(term) {
+            for _ in 0..self.count(key) {
+                taken.push(key, term.clone());
+            }
+        }
+        for child in children(term) {
+            self.clone_marked(child, taken);
+        }
+    }
+
+    fn count(&self, key: usize) -> usize {
+        self.counts.get(&key).cloned().unwrap_or(0)
+    }
+}
+
+/// The sub-terms moved out of a term by [`Marks::take_from`].
+#[derive(Debug)]
+pub struct Taken {
+    terms: HashMap<usize, Vec<Term>>,
+}
+impl Taken {
+    fn push(&mut self, key: usize, term: Term) {
+        self.terms.entry(key).or_default().push(term);
+    }
+
+    fn take(&mut self, key: usize) -> Term {
+        self.terms
+            .get_mut(&key)
+            .and_then(Vec::pop)
+            .expect("a sub-term is taken once for each mark")
+    }
+}
+
+/// Returns the addresses which identify the term, i.e. those of itself and its content.
+fn keys(term: &Term) -> Vec<usize> {
+    fn address<T>(x: &T) -> usize {
+        x as *const T as usize
+    }
+    let content = match *term {
+        Term::Atom(ref x) => address(x),
+        Term::FixInteger(ref x) => address(x),
+        Term::BigInteger(ref x) => address(x),
+        Term::Float(ref x) => address(x),
+        Term::Pid(ref x) => address(x),
+        Term::Port(ref x) => address(x),
+        Term::Reference(ref x) => address(x),
+        Term::ExternalFun(ref x) => address(x),
+        Term::InternalFun(ref x) => address(x),
+        Term::Binary(ref x) => address(x),
+        Term::BitBinary(ref x) => address(x),
+        Term::List(ref x) => address(x),
+        Term::ImproperList(ref x) => address(x),
+        Term::Tuple(ref x) => address(x),
+        Term::Map(ref x) => address(x),
+    };
+    let mut keys = vec![address(term), content];
+    keys.dedup();
+    keys
+}
+
+fn children(term: &Term) -> Vec<&Term> {
+    match *term {
+        Term::List(ref x) => x.elements.iter().collect(),
+        Term::ImproperList(ref x) => x.elements.iter().chain(Some(&*x.last)).collect(),
+        Term::Tuple(ref x) => x.elements.iter().collect(),
+        Term::Map(ref x) => x.entries.iter().flat_map(|(k, v)| vec![k, v]).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn children_mut(term: &mut Term) -> Vec<&mut Term> {
+    match *term {
+        Term::List(ref mut x) => x.elements.iter_mut().collect(),
+        Term::ImproperList(ref mut x) => x.elements.iter_mut().chain(Some(&mut *x.last)).collect(),
+        Term::Tuple(ref mut x) => x.elements.iter_mut().collect(),
+        Term::Map(ref mut x) => x.entries.iter_mut().flat_map(|(k, v)| vec![k, v]).collect(),
+        _ => Vec::new(),
+    }
 }
+
+/// Defers the conversion of a reference to a sub-term, which is moved out of the term
+/// unless it is not a part of the term.
+fn defer_ref<T, F>(x: &T, marks: &mut Marks, from_term: F) -> Deferred<T>
+where
+    T: Clone + 'static,
+    F: FnOnce(Term) -> Option<T> + 'static,
+{
+    match marks.mark(x) {
+        Some(key) => Box::new(move |taken| {
+            from_term(taken.take(key)).expect("a sub-term is taken as its own type")
+        }),
+        None => {
+            let x = x.clone();
+            Box::new(move |_| x)
+        }
+    }
+}
+impl<'a> IntoOwned for &'a Term {
+    type Owned = Term;
+    fn into_owned(self) -> Self::Owned {
+        self.clone()
+    }
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+        defer_ref(self, marks, Some)
+    }
+}
+macro_rules! impl_into_owned_term_ref {
+    ($($t:ident),+) => {
+        $(impl<'a> IntoOwned for &'a $t {
+            type Owned = $t;
+            fn into_owned(self) -> Self::Owned {
+                self.clone()
+            }
+            fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+                defer_ref(self, marks, |term| $t::try_from(term).ok())
+            }
+        })+
+    };
+}
+impl_into_owned_term_ref!(
+    Atom,
+    FixInteger,
+    BigInteger,
+    Float,
+    Pid,
+    Port,
+    Reference,
+    ExternalFun,
+    InternalFun,
+    Binary,
+    BitBinary,
+    List,
+    ImproperList,
+    Tuple,
+    Map
+);
+impl<'a> IntoOwned for &'a str {
+    type Owned = String;
+    fn into_owned(self) -> Self::Owned {
+        self.to_owned()
+    }
+}
+impl<'a, T: Clone + 'static> IntoOwned for &'a [T] {
+    type Owned = Vec<T>;
+    fn into_owned(self) -> Self::Owned {
+        self.to_vec()
+    }
+}
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+        let deferred = self.into_iter().map(|x| x.defer(marks)).collect::<Vec<_>>();
+        Box::new(move |taken| deferred.into_iter().map(|f| f(taken)).collect())
+    }
+}
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+        match self {
+            Some(x) => {
+                let f = x.defer(marks);
+                Box::new(move |taken| Some(f(taken)))
+            }
+            None => Box::new(|_| None),
+        }
+    }
+}
+impl<'a> IntoOwned for crate::elixir::Struct<'a> {
+    type Owned = Map;
+    fn into_owned(self) -> Self::Owned {
+        self.as_map().clone()
+    }
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+        self.as_map().defer(marks)
+    }
+}
+macro_rules! impl_into_owned_identity {
+    ($($t:ty),+) => {
+        $(impl IntoOwned for $t {
+            type Owned = Self;
+            fn into_owned(self) -> Self::Owned {
+                self
+            }
+        })+
+    };
+}
+impl_into_owned_identity!(
+    (),
+    bool,
+    char,
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    u128,
+    i128,
+    usize,
+    isize,
+    f32,
+    f64,
+    String,
+    num::BigInt,
+    num::BigUint,
+    Term,
+    Map
+);
+macro_rules! impl_into_owned_tuple {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: IntoOwned),+> IntoOwned for ($($t,)+) {
+            type Owned = ($($t::Owned,)+);
+            fn into_owned(self) -> Self::Owned {
+                ($(self.$i.into_owned(),)+)
+            }
+            fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+                let deferred = ($(self.$i.defer(marks),)+);
+                Box::new(move |taken| ($((deferred.$i)(taken),)+))
+            }
+        }
+    };
+}
+impl_into_owned_tuple!(T0 0);
+impl_into_owned_tuple!(T0 0, T1 1);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14);
+impl_into_owned_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15);
+
+macro_rules! define_union {
+    ($name:ident; $($v:ident),+) => {
+        #[derive(Debug, Clone)]
+        pub enum $name<$($v),+> {
+            $($v($v)),+
+        }
+        impl<$($v: IntoOwned),+> IntoOwned for $name<$($v),+> {
+            type Owned = $name<$($v::Owned),+>;
+            fn into_owned(self) -> Self::Owned {
+                match self {
+                    $($name::$v(x) => $name::$v(x.into_owned())),+
+                }
+            }
+            fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+                match self {
+                    $($name::$v(x) => {
+                        let f = x.defer(marks);
+                        Box::new(move |taken| $name::$v(f(taken)))
+                    }),+
+                }
+            }
+        }
+    };
+}
+define_union!(Union2; A, B);
 impl<A, B> Union2<A, B> {
     pub fn is_a(&self) -> bool {
         match *self {
@@ -80,40 +711,20 @@ impl<A, B> Union2<A, B> {
         }
     }
 }
-
-#[derive(Debug, Clone)]
-pub enum Union3<A, B, C> {
-    A(A),
-    B(B),
-    C(C),
-}
-
-#[derive(Debug, Clone)]
-pub enum Union4<A, B, C, D> {
-    A(A),
-    B(B),
-    C(C),
-    D(D),
-}
-
-#[derive(Debug, Clone)]
-pub enum Union5<A, B, C, D, E> {
-    A(A),
-    B(B),
-    C(C),
-    D(D),
-    E(E),
-}
-
-#[derive(Debug, Clone)]
-pub enum Union6<A, B, C, D, E, F> {
-    A(A),
-    B(B),
-    C(C),
-    D(D),
-    E(E),
-    F(F),
-}
+define_union!(Union3; A, B, C);
+define_union!(Union4; A, B, C, D);
+define_union!(Union5; A, B, C, D, E);
+define_union!(Union6; A, B, C, D, E, F);
+define_union!(Union7; A, B, C, D, E, F, G);
+define_union!(Union8; A, B, C, D, E, F, G, H);
+define_union!(Union9; A, B, C, D, E, F, G, H, I);
+define_union!(Union10; A, B, C, D, E, F, G, H, I, J);
+define_union!(Union11; A, B, C, D, E, F, G, H, I, J, K);
+define_union!(Union12; A, B, C, D, E, F, G, H, I, J, K, L);
+define_union!(Union13; A, B, C, D, E, F, G, H, I, J, K, L, M);
+define_union!(Union14; A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+define_union!(Union15; A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+define_union!(Union16; A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
 
 #[derive(Debug, Clone)]
 pub struct Any<T>(::std::marker::PhantomData<T>);
@@ -140,6 +751,9 @@ where
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.try_as_ref().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        type_spec::<O>()
+    }
 }
 
 impl<'a> Pattern<'a> for &'static str {
@@ -151,6 +765,9 @@ impl<'a> Pattern<'a> for &'static str {
             .ok_or_else(|| self.unmatched(input)));
         Ok(*self)
     }
+    fn spec(&self) -> Spec {
+        Spec::Atom((*self).to_owned())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -171,10 +788,27 @@ where
         }
         Ok(outputs)
     }
+    fn spec(&self) -> Spec {
+        Spec::List(Box::new(self.0.spec()))
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Implements `Debug` for a wrapper of a tuple, which `std` only does up to 12 elements.
+macro_rules! impl_wrapper_debug {
+    ($name:ident; $($p:ident $i:tt),+) => {
+        impl<$($p: Debug),+> Debug for $name<($($p,)+)> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut t = f.debug_tuple(stringify!($name));
+                $(t.field(&(self.0).$i);)+
+                t.finish()
+            }
+        }
+    };
+}
+
+#[derive(Clone)]
 pub struct FixList<T>(pub T);
+impl_wrapper_debug!(FixList; P0 0);
 impl<'a, P0> Pattern<'a> for FixList<(P0,)>
 where
     P0: Pattern<'a> + 'static,
@@ -192,526 +826,1485 @@ where
             .map_err(|e| self.unmatched(input).cause(e)));
         Ok(o0)
     }
+    fn spec(&self) -> Spec {
+        Spec::FixedList(vec![(self.0).0.spec()])
+    }
 }
 
-impl<'a, P0, P1> Pattern<'a> for FixList<(P0, P1)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output);
+macro_rules! impl_fix_list {
+    ($len:expr; $($p:ident $i:tt),+) => {
+        impl<'a, $($p),+> Pattern<'a> for FixList<($($p,)+)>
+        where
+            $($p: Pattern<'a> + 'static),+
+        {
+            type Output = ($($p::Output,)+);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let l: &List = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+                let e = &l.elements;
+                if e.len() != $len {
+                    return Err(self.unmatched(input));
+                }
+                Ok(($(
+                    (self.0).$i
+                        .try_match(&e[$i])
+                        .map_err(|cause| self.unmatched(input).cause(cause))?,
+                )+))
+            }
+            fn spec(&self) -> Spec {
+                Spec::FixedList(vec![$((self.0).$i.spec()),+])
+            }
+        }
+        impl_wrapper_debug!(FixList; $($p $i),+);
+    };
+}
+impl_fix_list!(2; P0 0, P1 1);
+impl_fix_list!(3; P0 0, P1 1, P2 2);
+impl_fix_list!(4; P0 0, P1 1, P2 2, P3 3);
+impl_fix_list!(5; P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_fix_list!(6; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+impl_fix_list!(7; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+impl_fix_list!(8; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+impl_fix_list!(9; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8);
+impl_fix_list!(10; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9);
+impl_fix_list!(11; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10);
+impl_fix_list!(12; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11);
+impl_fix_list!(13; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12);
+impl_fix_list!(14; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13);
+impl_fix_list!(15; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14);
+impl_fix_list!(16; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14, P15 15);
+
+/// A list which starts with elements matching the given patterns, like `[H1, H2 | T]`.
+///
+/// The output is the tuple of the outputs of the elements and the [`Tail`] after them.
+/// Improper lists are also matched.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::{Prefix, U8};
+///
+/// let term: Term = "[1,2,3]".parse().unwrap();
+/// let ((a,), tail) = term.as_match(Prefix((U8,))).unwrap();
+/// assert_eq!((1, "[2,3]".to_owned()), (a, tail.to_string()));
+///
+/// let term: Term = "[1|foo]".parse().unwrap();
+/// let (_, tail) = term.as_match(Prefix((U8,))).unwrap();
+/// assert_eq!("foo", tail.to_string());
+/// ```
+#[derive(Clone)]
+pub struct Prefix<T>(pub T);
+macro_rules! impl_prefix {
+    ($len:expr; $($p:ident $i:tt),+) => {
+        impl<'a, $($p),+> Pattern<'a> for Prefix<($($p,)+)>
+        where
+            $($p: Pattern<'a> + 'static),+
+        {
+            type Output = (($($p::Output,)+), Tail<'a>);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let (e, last) = match *input {
+                    Term::List(ref x) => (&x.elements[..], None),
+                    Term::ImproperList(ref x) => (&x.elements[..], Some(&*x.last)),
+                    _ => return Err(self.unmatched(input)),
+                };
+                if e.len() < $len {
+                    return Err(self.unmatched(input));
+                }
+                let heads = ($(
+                    (self.0).$i
+                        .try_match(&e[$i])
+                        .map_err(|cause| self.unmatched(input).cause(cause))?,
+                )+);
+                let tail = Tail {
+                    elements: &e[$len..],
+                    last,
+                };
+                Ok((heads, tail))
+            }
+            fn spec(&self) -> Spec {
+                Spec::Builtin("nonempty_maybe_improper_list()")
+            }
+        }
+        impl_wrapper_debug!(Prefix; $($p $i),+);
+    };
+}
+impl_prefix!(1; P0 0);
+impl_prefix!(2; P0 0, P1 1);
+impl_prefix!(3; P0 0, P1 1, P2 2);
+impl_prefix!(4; P0 0, P1 1, P2 2, P3 3);
+impl_prefix!(5; P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_prefix!(6; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+impl_prefix!(7; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+impl_prefix!(8; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+impl_prefix!(9; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8);
+impl_prefix!(10; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9);
+impl_prefix!(11; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10);
+impl_prefix!(12; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11);
+impl_prefix!(13; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12);
+impl_prefix!(14; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13);
+impl_prefix!(15; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14);
+impl_prefix!(16; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14, P15 15);
+
+/// The tail of a list after some of its elements, as matched by [`Prefix`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tail<'a> {
+    /// The remaining elements.
+    pub elements: &'a [Term],
+
+    /// The last tail of an improper list, or `None` for a proper list.
+    pub last: Option<&'a Term>,
+}
+impl<'a> Tail<'a> {
+    /// Makes the term of the tail, which is a list unless only the last tail of an
+    /// improper list remains.
+    pub fn to_term(&self) -> Term {
+        match (self.elements, self.last) {
+            ([], None) => Term::from(List::nil()),
+            ([], Some(last)) => last.clone(),
+            (elements, None) => Term::from(List::from(elements.to_vec())),
+            (elements, Some(last)) => {
+                Term::from(ImproperList::from((elements.to_vec(), last.clone())))
+            }
+        }
+    }
+}
+impl<'a> fmt::Display for Tail<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_term(), f)
+    }
+}
+impl<'a> IntoOwned for Tail<'a> {
+    type Owned = Term;
+    fn into_owned(self) -> Self::Owned {
+        self.to_term()
+    }
+    fn defer(self, marks: &mut Marks) -> Deferred<Self::Owned> {
+        let elements = self.elements.iter().collect::<Vec<_>>().defer(marks);
+        let last = self.last.defer(marks);
+        Box::new(move |taken| match (elements(taken), last(taken)) {
+            (elements, None) => Term::from(List::from(elements)),
+            (ref elements, Some(last)) if elements.is_empty() => last,
+            (elements, Some(last)) => Term::from(ImproperList::from((elements, last))),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Nil;
+impl<'a> Pattern<'a> for Nil {
+    type Output = &'a [Term];
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &l.elements;
-        r#try!((e.len() == 2)
+        r#try!((l.elements.len() == 0)
             .as_option()
             .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!((self.0)
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!((self.0)
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1))
+        Ok(&l.elements)
+    }
+    fn spec(&self) -> Spec {
+        Spec::FixedList(Vec::new())
     }
 }
 
-impl<'a, P0, P1, P2> Pattern<'a> for FixList<(P0, P1, P2)>
+#[derive(Debug, Clone)]
+pub struct Cons<H, T>(pub H, pub T);
+impl<'a, P0, P1> Pattern<'a> for Cons<P0, P1>
 where
     P0: Pattern<'a> + 'static,
     P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
 {
-    type Output = (P0::Output, P1::Output, P2::Output);
+    type Output = (P0::Output, Vec<P1::Output>);
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
         let e = &l.elements;
-        r#try!((e.len() == 3)
+        r#try!((e.len() > 0)
             .as_option()
             .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!((self.0)
+        let h = r#try!(self
             .0
             .try_match(&e[0])
             .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!((self.0)
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!((self.0)
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2))
+
+        let mut tail = Vec::with_capacity(l.elements.len() - 1);
+        for e in &l.elements[1..] {
+            tail.push(r#try!(self
+                .1
+                .try_match(e)
+                .map_err(|e| self.unmatched(input).cause(e))));
+        }
+        Ok((h, tail))
+    }
+    fn spec(&self) -> Spec {
+        Spec::NonEmptyList(Box::new(Spec::union(vec![self.0.spec(), self.1.spec()])))
     }
 }
 
-impl<'a, P0, P1, P2, P3> Pattern<'a> for FixList<(P0, P1, P2, P3)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output, P2::Output, P3::Output);
+impl<'a> Pattern<'a> for () {
+    type Output = ();
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &l.elements;
-        r#try!((e.len() == 4)
+        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
+        r#try!((t.elements.len() == 0)
             .as_option()
             .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!((self.0)
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!((self.0)
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!((self.0)
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!((self.0)
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3))
+        Ok(())
+    }
+    fn spec(&self) -> Spec {
+        Spec::Tuple(Vec::new())
     }
 }
 
-impl<'a, P0, P1, P2, P3, P4> Pattern<'a> for FixList<(P0, P1, P2, P3, P4)>
+impl<'a, P0> Pattern<'a> for (P0,)
 where
     P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
 {
-    type Output = (P0::Output, P1::Output, P2::Output, P3::Output, P4::Output);
+    type Output = P0::Output;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &l.elements;
-        r#try!((e.len() == 5)
+        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
+        r#try!((t.elements.len() == 1)
             .as_option()
             .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!((self.0)
+        let o0 = r#try!(self
             .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!((self.0)
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!((self.0)
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!((self.0)
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o4 = r#try!((self.0)
-            .4
-            .try_match(&e[4])
+            .try_match(&t.elements[0])
             .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3, o4))
+        Ok(o0)
+    }
+    fn spec(&self) -> Spec {
+        Spec::Tuple(vec![self.0.spec()])
     }
 }
 
-impl<'a, P0, P1, P2, P3, P4, P5> Pattern<'a> for FixList<(P0, P1, P2, P3, P4, P5)>
+macro_rules! impl_tuple {
+    ($len:expr; $($p:ident $i:tt),+) => {
+        impl<'a, $($p),+> Pattern<'a> for ($($p,)+)
+        where
+            $($p: Pattern<'a> + 'static),+
+        {
+            type Output = ($($p::Output,)+);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let t: &Tuple = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+                let e = &t.elements;
+                if e.len() != $len {
+                    return Err(self.unmatched(input));
+                }
+                Ok(($(
+                    self.$i
+                        .try_match(&e[$i])
+                        .map_err(|cause| self.unmatched(input).cause(cause))?,
+                )+))
+            }
+            fn spec(&self) -> Spec {
+                Spec::Tuple(vec![$(self.$i.spec()),+])
+            }
+        }
+    };
+}
+// `Debug` (required by `Pattern`) is implemented for tuples of up to 12 elements.
+impl_tuple!(2; P0 0, P1 1);
+impl_tuple!(3; P0 0, P1 1, P2 2);
+impl_tuple!(4; P0 0, P1 1, P2 2, P3 3);
+impl_tuple!(5; P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_tuple!(6; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+impl_tuple!(7; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+impl_tuple!(8; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+impl_tuple!(9; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8);
+impl_tuple!(10; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9);
+impl_tuple!(11; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10);
+impl_tuple!(12; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11);
+
+/// A tuple of up to 16 elements.
+///
+/// Plain Rust tuples can be used as patterns of up to 12 elements.
+#[derive(Clone)]
+pub struct TupleOf<T>(pub T);
+macro_rules! impl_tuple_of {
+    ($len:expr; $($p:ident $i:tt),+) => {
+        impl<'a, $($p),+> Pattern<'a> for TupleOf<($($p,)+)>
+        where
+            $($p: Pattern<'a> + 'static),+
+        {
+            type Output = ($($p::Output,)+);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let t: &Tuple = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+                let e = &t.elements;
+                if e.len() != $len {
+                    return Err(self.unmatched(input));
+                }
+                Ok(($(
+                    (self.0).$i
+                        .try_match(&e[$i])
+                        .map_err(|cause| self.unmatched(input).cause(cause))?,
+                )+))
+            }
+            fn spec(&self) -> Spec {
+                Spec::Tuple(vec![$((self.0).$i.spec()),+])
+            }
+        }
+        impl_wrapper_debug!(TupleOf; $($p $i),+);
+    };
+}
+impl_tuple_of!(1; P0 0);
+impl_tuple_of!(2; P0 0, P1 1);
+impl_tuple_of!(3; P0 0, P1 1, P2 2);
+impl_tuple_of!(4; P0 0, P1 1, P2 2, P3 3);
+impl_tuple_of!(5; P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_tuple_of!(6; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+impl_tuple_of!(7; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+impl_tuple_of!(8; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+impl_tuple_of!(9; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8);
+impl_tuple_of!(10; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9);
+impl_tuple_of!(11; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10);
+impl_tuple_of!(12; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11);
+impl_tuple_of!(13; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12);
+impl_tuple_of!(14; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13);
+impl_tuple_of!(15; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14);
+impl_tuple_of!(16; P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7, P8 8, P9 9, P10 10, P11 11, P12 12, P13 13, P14 14, P15 15);
+
+macro_rules! try_err {
+    ($e:expr) => {
+        match $e {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        }
+    };
+}
+
+#[derive(Clone)]
+pub struct Or<T>(pub T);
+macro_rules! impl_or {
+    ($union:ident; $($p:ident $i:tt $v:ident),+) => {
+        impl<'a, $($p),+> Pattern<'a> for Or<($($p,)+)>
+        where
+            $($p: Pattern<'a> + 'static),+
+        {
+            type Output = $union<$($p::Output),+>;
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let errors = vec![$(
+                    try_err!((self.0).$i.try_match(input).map($union::$v)),
+                )+];
+                Err(self.unmatched(input).alternatives(errors))
+            }
+            fn spec(&self) -> Spec {
+                Spec::union(vec![$((self.0).$i.spec()),+])
+            }
+        }
+        impl_wrapper_debug!(Or; $($p $i),+);
+    };
+}
+impl_or!(Union2; P0 0 A, P1 1 B);
+impl_or!(Union3; P0 0 A, P1 1 B, P2 2 C);
+impl_or!(Union4; P0 0 A, P1 1 B, P2 2 C, P3 3 D);
+impl_or!(Union5; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E);
+impl_or!(Union6; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F);
+impl_or!(Union7; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G);
+impl_or!(Union8; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H);
+impl_or!(Union9; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I);
+impl_or!(Union10; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J);
+impl_or!(Union11; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K);
+impl_or!(Union12; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K, P11 11 L);
+impl_or!(Union13; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K, P11 11 L, P12 12 M);
+impl_or!(Union14; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K, P11 11 L, P12 12 M, P13 13 N);
+impl_or!(Union15; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K, P11 11 L, P12 12 M, P13 13 N, P14 14 O);
+impl_or!(Union16; P0 0 A, P1 1 B, P2 2 C, P3 3 D, P4 4 E, P5 5 F, P6 6 G, P7 7 H, P8 8 I, P9 9 J, P10 10 K, P11 11 L, P12 12 M, P13 13 N, P14 14 O, P15 15 P);
+
+/// A pattern with a guard, made by [`Pattern::where_`].
+#[derive(Clone)]
+pub struct Where<P, F> {
+    pattern: P,
+    guard: F,
+    name: &'static str,
+}
+impl<P, F> Where<P, F> {
+    /// Names the guard, for `Unmatch`.
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+}
+impl<P: Debug, F> Debug for Where<P, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Where")
+            .field("pattern", &self.pattern)
+            .field("guard", &self.name)
+            .finish()
+    }
+}
+impl<'a, P, F> Pattern<'a> for Where<P, F>
 where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
-    P5: Pattern<'a> + 'static,
+    P: Pattern<'a> + 'static,
+    F: Fn(&P::Output) -> bool + Clone + 'static,
 {
-    type Output = (
-        P0::Output,
-        P1::Output,
-        P2::Output,
-        P3::Output,
-        P4::Output,
-        P5::Output,
-    );
+    type Output = P::Output;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &l.elements;
-        r#try!((e.len() == 6)
-            .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!((self.0)
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!((self.0)
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!((self.0)
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!((self.0)
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o4 = r#try!((self.0)
-            .4
-            .try_match(&e[4])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o5 = r#try!((self.0)
-            .5
-            .try_match(&e[5])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3, o4, o5))
+        let o = self
+            .pattern
+            .try_match(input)
+            .map_err(|e| self.unmatched(input).cause(e))?;
+        if (self.guard)(&o) {
+            Ok(o)
+        } else {
+            Err(self.unmatched(input))
+        }
+    }
+    fn spec(&self) -> Spec {
+        self.pattern.spec()
+    }
+}
+
+/// A pattern whose output is transformed, made by [`Pattern::map`].
+#[derive(Clone)]
+pub struct Mapped<P, F> {
+    pattern: P,
+    f: F,
+}
+impl<P: Debug, F> Debug for Mapped<P, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Mapped")
+            .field("pattern", &self.pattern)
+            .field("f", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+impl<'a, P, F, T> Pattern<'a> for Mapped<P, F>
+where
+    P: Pattern<'a> + 'static,
+    F: Fn(P::Output) -> T + Clone + 'static,
+{
+    type Output = T;
+    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+        self.pattern
+            .try_match(input)
+            .map(&self.f)
+            .map_err(|e| self.unmatched(input).cause(e))
+    }
+    fn spec(&self) -> Spec {
+        self.pattern.spec()
+    }
+}
+
+/// An integer (or a float, for `f64` ranges) within a range.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::Range;
+///
+/// let term: Term = "42".parse().unwrap();
+/// assert_eq!(42, term.as_match(Range(1..100)).unwrap());
+/// assert!(term.as_match(Range(1u8..=10)).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Range<R>(pub R);
+macro_rules! impl_range {
+    ($t:ty, $to:ident, $spec:ident) => {
+        impl<'a> Pattern<'a> for Range<std::ops::Range<$t>> {
+            type Output = $t;
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                input
+                    .$to()
+                    .filter(|x| self.0.contains(x))
+                    .ok_or_else(|| self.unmatched(input))
+            }
+            fn spec(&self) -> Spec {
+                $spec(self.0.start.to_i128(), self.0.end.to_i128().map(|x| x - 1))
+            }
+        }
+        impl<'a> Pattern<'a> for Range<std::ops::RangeInclusive<$t>> {
+            type Output = $t;
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                input
+                    .$to()
+                    .filter(|x| self.0.contains(x))
+                    .ok_or_else(|| self.unmatched(input))
+            }
+            fn spec(&self) -> Spec {
+                $spec(self.0.start().to_i128(), self.0.end().to_i128())
+            }
+        }
+    };
+}
+impl_range!(i8, to_i8, integer_spec);
+impl_range!(i16, to_i16, integer_spec);
+impl_range!(i32, to_i32, integer_spec);
+impl_range!(i64, to_i64, integer_spec);
+impl_range!(u8, to_u8, integer_spec);
+impl_range!(u16, to_u16, integer_spec);
+impl_range!(u32, to_u32, integer_spec);
+impl_range!(u64, to_u64, integer_spec);
+impl_range!(f64, to_f64, float_spec);
+
+/// A term which is equal to the given one.
+#[derive(Debug, Clone)]
+pub struct Eq(pub Term);
+impl<'a> Pattern<'a> for Eq {
+    type Output = &'a Term;
+    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+        if *input == self.0 {
+            Ok(input)
+        } else {
+            Err(self.unmatched(input))
+        }
+    }
+    fn spec(&self) -> Spec {
+        Spec::literal(self.0.clone())
+    }
+}
+
+/// The first of the given patterns which matches, like [`Or`] with patterns of the same type.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::OneOf;
+///
+/// let term: Term = "info".parse().unwrap();
+/// assert_eq!("info", term.as_match(OneOf(["debug", "info", "warning"])).unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OneOf<T>(pub T);
+impl<'a, P, const N: usize> Pattern<'a> for OneOf<[P; N]>
+where
+    P: Pattern<'a> + 'static,
+{
+    type Output = P::Output;
+    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+        one_of(&self.0, input).map_err(|e| self.unmatched(input).alternatives(e))
+    }
+    fn spec(&self) -> Spec {
+        Spec::union(self.0.iter().map(Pattern::spec).collect())
+    }
+}
+impl<'a, P> Pattern<'a> for OneOf<Vec<P>>
+where
+    P: Pattern<'a> + 'static,
+{
+    type Output = P::Output;
+    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+        one_of(&self.0, input).map_err(|e| self.unmatched(input).alternatives(e))
+    }
+    fn spec(&self) -> Spec {
+        Spec::union(self.0.iter().map(Pattern::spec).collect())
+    }
+}
+
+fn one_of<'a, P: Pattern<'a>>(
+    patterns: &[P],
+    input: &'a Term,
+) -> std::result::Result<P::Output, Vec<Unmatch<'a>>> {
+    let mut errors = Vec::new();
+    for p in patterns {
+        errors.push(try_err!(p.try_match(input)));
+    }
+    Err(errors)
+}
+
+/// A term which does not match the given pattern.
+#[derive(Debug, Clone)]
+pub struct Not<P>(pub P);
+impl<'a, P> Pattern<'a> for Not<P>
+where
+    P: Pattern<'a> + 'static,
+{
+    type Output = &'a Term;
+    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+        match self.0.try_match(input) {
+            Ok(_) => Err(self.unmatched(input)),
+            Err(_) => Ok(input),
+        }
+    }
+}
+
+/// An entry of a [`MapWith`] pattern.
+///
+/// It is implemented for `(K, P)`, which requires the key `K` with a value matching `P`,
+/// and for `(K, Optional<P>)`, which also accepts maps without `K`.
+pub trait MapEntry<'a>: Debug + Clone {
+    type Output;
+
+    /// Matches the entry against `map`, returning `None` if a required key is missing.
+    fn match_entry(&self, map: &'a Map) -> Option<Result<'a, Self::Output>>;
+
+    /// Describes the entry (see [`Pattern::spec`]).
+    fn spec(&self) -> MapEntrySpec {
+        any_entry()
+    }
+}
+impl<'a, K, P> MapEntry<'a> for (K, P)
+where
+    K: Key + Debug + Clone,
+    P: Pattern<'a>,
+{
+    type Output = P::Output;
+    fn match_entry(&self, map: &'a Map) -> Option<Result<'a, Self::Output>> {
+        map_value(map, &self.0).map(|v| self.1.try_match(v))
+    }
+    fn spec(&self) -> MapEntrySpec {
+        MapEntrySpec {
+            key: key_spec(&self.0),
+            value: self.1.spec(),
+            required: true,
+        }
+    }
+}
+impl<'a, K, P> MapEntry<'a> for (K, Optional<P>)
+where
+    K: Key + Debug + Clone,
+    P: Pattern<'a>,
+{
+    type Output = Option<P::Output>;
+    fn match_entry(&self, map: &'a Map) -> Option<Result<'a, Self::Output>> {
+        match map_value(map, &self.0) {
+            Some(v) => Some((self.1).0.try_match(v).map(Some)),
+            None => Some(Ok(None)),
+        }
+    }
+    fn spec(&self) -> MapEntrySpec {
+        MapEntrySpec {
+            key: key_spec(&self.0),
+            value: (self.1).0.spec(),
+            required: false,
+        }
+    }
+}
+
+fn key_spec<K: Key>(key: &K) -> Spec {
+    key.to_term().map_or(Spec::Any, Spec::literal)
+}
+
+fn any_entry() -> MapEntrySpec {
+    MapEntrySpec {
+        key: Spec::Any,
+        value: Spec::Any,
+        required: false,
+    }
+}
+
+fn map_value<'a, K: Key>(map: &'a Map, key: &K) -> Option<&'a Term> {
+    map.entries
+        .iter()
+        .find(|(k, _)| key.matches(k))
+        .map(|(_, v)| v)
+}
+
+/// The value of an optional [`MapWith`] entry, which is `None` if the key is missing.
+#[derive(Debug, Clone)]
+pub struct Optional<P>(pub P);
+
+/// A map which has the given entries, and possibly others.
+///
+/// The output is a tuple of the outputs of the entries.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::{MapWith, Optional, U8};
+///
+/// let term: Term = "#{id => 1,name => <<\"a\">>,extra => x}".parse().unwrap();
+/// let (id, age) = term.as_match(MapWith((("id", U8), ("age", Optional(U8))))).unwrap();
+/// assert_eq!((1, None), (id, age));
+/// ```
+#[derive(Clone)]
+pub struct MapWith<T>(pub T);
+macro_rules! impl_map_with {
+    ($($e:ident $i:tt),+) => {
+        impl<'a, $($e),+> Pattern<'a> for MapWith<($($e,)+)>
+        where
+            $($e: MapEntry<'a> + 'static),+
+        {
+            type Output = ($($e::Output,)+);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let m: &Map = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+                Ok(($(
+                    (self.0).$i
+                        .match_entry(m)
+                        .ok_or_else(|| self.unmatched(input))?
+                        .map_err(|e| self.unmatched(input).cause(e))?,
+                )+))
+            }
+            fn spec(&self) -> Spec {
+                Spec::Map(vec![$((self.0).$i.spec(),)+ any_entry()])
+            }
+        }
+        impl_wrapper_debug!(MapWith; $($e $i),+);
+    };
+}
+impl_map_with!(E0 0);
+impl_map_with!(E0 0, E1 1);
+impl_map_with!(E0 0, E1 1, E2 2);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10, E11 11);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10, E11 11, E12 12);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10, E11 11, E12 12, E13 13);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10, E11 11, E12 12, E13 13, E14 14);
+impl_map_with!(E0 0, E1 1, E2 2, E3 3, E4 4, E5 5, E6 6, E7 7, E8 8, E9 9, E10 10, E11 11, E12 12, E13 13, E14 14, E15 15);
+
+/// The size of a [`Bin`] segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    /// A fixed size.
+    Fixed(usize),
+
+    /// The value of the n-th (zero-based) earlier segment, like `Len` in
+    /// `<<Len:8, Data:Len/binary>>`, which must be a non-negative integer.
+    Field(usize),
+}
+impl From<usize> for Size {
+    fn from(n: usize) -> Self {
+        Size::Fixed(n)
+    }
+}
+
+/// The byte order of a [`Bin`] segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// The state of a [`Bin`] pattern being matched against a bitstring.
+#[derive(Debug)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    len: usize,
+    values: Vec<Option<u64>>,
+}
+impl<'a> BitReader<'a> {
+    fn new(input: &'a Term) -> Option<Self> {
+        let (bytes, len) = match *input {
+            Term::Binary(ref x) => (&x.bytes[..], x.bytes.len() * 8),
+            Term::BitBinary(ref x) => (&x.bytes[..], bit_len(x)),
+            _ => return None,
+        };
+        Some(BitReader {
+            bytes,
+            position: 0,
+            len,
+            values: Vec::new(),
+        })
+    }
+
+    /// Returns the number of the remaining bits.
+    pub fn remaining(&self) -> usize {
+        self.len - self.position
+    }
+
+    /// Resolves `size` with the values of the earlier segments.
+    pub fn size(&self, size: Size) -> Option<usize> {
+        match size {
+            Size::Fixed(n) => Some(n),
+            Size::Field(i) => self
+                .values
+                .get(i)
+                .cloned()
+                .and_then(|x| x)
+                .and_then(|x| usize::try_from(x).ok()),
+        }
+    }
+
+    /// Reads an unsigned big endian integer of `bits` (up to 64) bits.
+    pub fn read_bits(&mut self, bits: usize) -> Option<u64> {
+        if bits > 64 || bits > self.remaining() {
+            return None;
+        }
+        let mut value = 0;
+        for i in self.position..self.position + bits {
+            value = (value << 1) | u64::from(self.bit(i));
+        }
+        self.position += bits;
+        Some(value)
+    }
+
+    /// Reads `n` bytes, which must start at a byte boundary.
+    pub fn read_bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bits = n.checked_mul(8)?;
+        if self.position % 8 != 0 || bits > self.remaining() {
+            return None;
+        }
+        let start = self.position / 8;
+        self.position += bits;
+        Some(&self.bytes[start..start + n])
+    }
+
+    fn bit(&self, i: usize) -> u8 {
+        let mut byte = self.bytes[i / 8];
+        let tail = self.len % 8;
+        if tail != 0 && i / 8 == self.len / 8 {
+            byte <<= 8 - tail;
+        }
+        (byte >> (7 - i % 8)) & 1
+    }
+}
+
+/// A segment of a [`Bin`] pattern.
+pub trait Segment<'a>: Debug + Clone {
+    type Output;
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output>;
+
+    /// Returns the value which later segments can refer to by [`Size::Field`].
+    fn value(_output: &Self::Output) -> Option<u64> {
+        None
+    }
+
+    /// Returns the fixed number of bits of the segment, and the unit of its variable size
+    /// (`0` if the size is fixed), for [`Spec::Bits`].
+    fn bit_size(&self) -> (usize, usize) {
+        (0, 1)
+    }
+}
+
+/// An unsigned integer segment, like `X:Size/unsigned-integer`.
+///
+/// The size is in bits, and must be a multiple of 8 for little endian segments.
+#[derive(Debug, Clone)]
+pub struct UintSeg {
+    pub size: Size,
+    pub endian: Endian,
+}
+impl UintSeg {
+    pub fn new<S: Into<Size>>(size: S) -> Self {
+        UintSeg {
+            size: size.into(),
+            endian: Endian::Big,
+        }
+    }
+    pub fn little(mut self) -> Self {
+        self.endian = Endian::Little;
+        self
+    }
+}
+impl<'a> Segment<'a> for UintSeg {
+    type Output = u64;
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output> {
+        read_integer(reader, self.size, self.endian)
+    }
+    fn value(output: &Self::Output) -> Option<u64> {
+        Some(*output)
+    }
+    fn bit_size(&self) -> (usize, usize) {
+        segment_size(self.size, 1)
+    }
+}
+
+/// A signed integer segment, like `X:Size/signed-integer`.
+///
+/// The size is in bits, and must be a multiple of 8 for little endian segments.
+#[derive(Debug, Clone)]
+pub struct IntSeg {
+    pub size: Size,
+    pub endian: Endian,
+}
+impl IntSeg {
+    pub fn new<S: Into<Size>>(size: S) -> Self {
+        IntSeg {
+            size: size.into(),
+            endian: Endian::Big,
+        }
+    }
+    pub fn little(mut self) -> Self {
+        self.endian = Endian::Little;
+        self
+    }
+}
+impl<'a> Segment<'a> for IntSeg {
+    type Output = i64;
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output> {
+        let bits = reader.size(self.size)?;
+        let value = read_integer(reader, Size::Fixed(bits), self.endian)?;
+        if bits == 0 || bits == 64 || value >> (bits - 1) == 0 {
+            Some(value as i64)
+        } else {
+            Some((value | (!0 << bits)) as i64)
+        }
+    }
+    fn value(output: &Self::Output) -> Option<u64> {
+        u64::try_from(*output).ok()
+    }
+    fn bit_size(&self) -> (usize, usize) {
+        segment_size(self.size, 1)
+    }
+}
+
+/// A float segment, like `X:Size/float`.
+///
+/// The size is 32 or 64 bits.
+#[derive(Debug, Clone)]
+pub struct FloatSeg {
+    pub size: Size,
+    pub endian: Endian,
+}
+impl FloatSeg {
+    pub fn new<S: Into<Size>>(size: S) -> Self {
+        FloatSeg {
+            size: size.into(),
+            endian: Endian::Big,
+        }
+    }
+    pub fn little(mut self) -> Self {
+        self.endian = Endian::Little;
+        self
+    }
+}
+impl<'a> Segment<'a> for FloatSeg {
+    type Output = f64;
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output> {
+        let bits = reader.size(self.size)?;
+        let value = read_integer(reader, Size::Fixed(bits), self.endian)?;
+        match bits {
+            32 => Some(f64::from(f32::from_bits(value as u32))),
+            64 => Some(f64::from_bits(value)),
+            _ => None,
+        }
+    }
+    fn bit_size(&self) -> (usize, usize) {
+        segment_size(self.size, 1)
+    }
+}
+
+/// A binary segment, like `X:Size/binary`.
+///
+/// The size is in bytes, and the segment must start at a byte boundary.
+#[derive(Debug, Clone)]
+pub struct BinarySeg {
+    pub size: Size,
+}
+impl BinarySeg {
+    pub fn new<S: Into<Size>>(size: S) -> Self {
+        BinarySeg { size: size.into() }
+    }
+}
+impl<'a> Segment<'a> for BinarySeg {
+    type Output = &'a [u8];
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output> {
+        let n = reader.size(self.size)?;
+        reader.read_bytes(n)
+    }
+    fn bit_size(&self) -> (usize, usize) {
+        segment_size(self.size, 8)
+    }
+}
+
+/// The rest of a binary, like `Rest/binary` at the end of a pattern.
+///
+/// The rest must start at a byte boundary and consist of whole bytes.
+#[derive(Debug, Clone)]
+pub struct RestSeg;
+impl<'a> Segment<'a> for RestSeg {
+    type Output = &'a [u8];
+    fn read(&self, reader: &mut BitReader<'a>) -> Option<Self::Output> {
+        if reader.remaining() % 8 != 0 {
+            return None;
+        }
+        reader.read_bytes(reader.remaining() / 8)
+    }
+    fn bit_size(&self) -> (usize, usize) {
+        (0, 8)
+    }
+}
+
+fn segment_size(size: Size, unit: usize) -> (usize, usize) {
+    match size {
+        Size::Fixed(n) => (n * unit, 0),
+        Size::Field(_) => (0, unit),
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Nil;
-impl<'a> Pattern<'a> for Nil {
-    type Output = &'a [Term];
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        r#try!((l.elements.len() == 0)
-            .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        Ok(&l.elements)
+/// Returns the number of bits of `x`, which is zero if it has no bytes.
+fn bit_len(x: &BitBinary) -> usize {
+    match x.bytes.len() {
+        0 => 0,
+        n => (n - 1) * 8 + x.tail_bits_size as usize,
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Cons<H, T>(pub H, pub T);
-impl<'a, P0, P1> Pattern<'a> for Cons<P0, P1>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, Vec<P1::Output>);
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let l: &List = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &l.elements;
-        r#try!((e.len() > 0)
-            .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let h = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-
-        let mut tail = Vec::with_capacity(l.elements.len() - 1);
-        for e in &l.elements[1..] {
-            tail.push(r#try!(self
-                .1
-                .try_match(e)
-                .map_err(|e| self.unmatched(input).cause(e))));
+fn read_integer(reader: &mut BitReader, size: Size, endian: Endian) -> Option<u64> {
+    let bits = reader.size(size)?;
+    let value = reader.read_bits(bits)?;
+    match endian {
+        Endian::Big => Some(value),
+        Endian::Little if bits % 8 == 0 => {
+            let n = bits / 8;
+            Some((0..n).fold(0, |acc, i| {
+                acc | ((value >> (8 * i)) & 0xFF) << (8 * (n - 1 - i))
+            }))
         }
-        Ok((h, tail))
+        Endian::Little => None,
     }
 }
 
-impl<'a> Pattern<'a> for () {
-    type Output = ();
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        r#try!((t.elements.len() == 0)
-            .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        Ok(())
-    }
+/// A binary or bitstring which consists of the given segments, like Erlang's bit syntax.
+///
+/// The segments must cover the whole input. The output is a tuple of the segment values.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::{Bin, BinarySeg, RestSeg, Size, UintSeg};
+///
+/// // <<Version:4, Flags:4, Len:16/little, Data:Len/binary, Rest/binary>>
+/// let term: Term = "<<16#12,3,0,\"abcde\">>".parse().unwrap();
+/// let pattern = Bin((
+///     UintSeg::new(4),
+///     UintSeg::new(4),
+///     UintSeg::new(16).little(),
+///     BinarySeg::new(Size::Field(2)),
+///     RestSeg,
+/// ));
+/// let (version, flags, _, data, rest) = term.as_match(pattern).unwrap();
+/// assert_eq!((1, 2), (version, flags));
+/// assert_eq!((&b"abc"[..], &b"de"[..]), (data, rest));
+/// ```
+#[derive(Clone)]
+pub struct Bin<T>(pub T);
+macro_rules! impl_bin {
+    ($($s:ident $i:tt),+) => {
+        impl<'a, $($s),+> Pattern<'a> for Bin<($($s,)+)>
+        where
+            $($s: Segment<'a> + 'static),+
+        {
+            type Output = ($($s::Output,)+);
+            fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
+                let mut reader = BitReader::new(input).ok_or_else(|| self.unmatched(input))?;
+                let output = ($({
+                    let o = (self.0).$i.read(&mut reader).ok_or_else(|| self.unmatched(input))?;
+                    reader.values.push($s::value(&o));
+                    o
+                },)+);
+                if reader.remaining() == 0 {
+                    Ok(output)
+                } else {
+                    Err(self.unmatched(input))
+                }
+            }
+            fn spec(&self) -> Spec {
+                let (size, unit) = [$((self.0).$i.bit_size()),+]
+                    .iter()
+                    .fold((0, 0), |(size, unit), &(s, u)| (size + s, gcd(unit, u)));
+                Spec::Bits { size, unit }
+            }
+        }
+        impl_wrapper_debug!(Bin; $($s $i),+);
+    };
 }
+impl_bin!(S0 0);
+impl_bin!(S0 0, S1 1);
+impl_bin!(S0 0, S1 1, S2 2);
+impl_bin!(S0 0, S1 1, S2 2, S3 3);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10, S11 11);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10, S11 11, S12 12);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10, S11 11, S12 12, S13 13);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10, S11 11, S12 12, S13 13, S14 14);
+impl_bin!(S0 0, S1 1, S2 2, S3 3, S4 4, S5 5, S6 6, S7 7, S8 8, S9 9, S10 10, S11 11, S12 12, S13 13, S14 14, S15 15);
 
-impl<'a, P0> Pattern<'a> for (P0,)
-where
-    P0: Pattern<'a> + 'static,
-{
-    type Output = P0::Output;
+#[derive(Debug, Clone)]
+pub struct ElixirStruct(pub &'static str);
+impl<'a> Pattern<'a> for ElixirStruct {
+    type Output = crate::elixir::Struct<'a>;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        r#try!((t.elements.len() == 1)
-            .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&t.elements[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok(o0)
+        let m: &Map = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        m.as_elixir_struct()
+            .filter(|s| s.module() == self.0)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Map(vec![
+            MapEntrySpec {
+                key: Spec::Atom("__struct__".to_owned()),
+                value: Spec::Atom(format!("Elixir.{}", self.0)),
+                required: true,
+            },
+            any_entry(),
+        ])
     }
 }
 
-impl<'a, P0, P1> Pattern<'a> for (P0, P1)
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output);
+/// A pid whose fields are optionally constrained.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::{pid, reference};
+///
+/// let term: Term = "{<foo@bar.1.2>,#Ref<foo@bar.1.2.3>}".parse().unwrap();
+/// let (p, _) = term.as_match((pid().node("foo@bar"), reference())).unwrap();
+/// assert_eq!(1, p.id);
+/// assert!(term.as_match((pid().node("nonode@nohost"), reference())).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PidPattern {
+    pub node: Option<&'static str>,
+    pub id: Option<u32>,
+    pub serial: Option<u32>,
+    pub creation: Option<u8>,
+}
+pub fn pid() -> PidPattern {
+    PidPattern::default()
+}
+impl PidPattern {
+    pub fn node(mut self, node: &'static str) -> Self {
+        self.node = Some(node);
+        self
+    }
+    pub fn id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn serial(mut self, serial: u32) -> Self {
+        self.serial = Some(serial);
+        self
+    }
+    pub fn creation(mut self, creation: u8) -> Self {
+        self.creation = Some(creation);
+        self
+    }
+}
+impl<'a> Pattern<'a> for PidPattern {
+    type Output = &'a Pid;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &t.elements;
-        r#try!((e.len() == 2)
+        let p: &Pid = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let matched = is_some_eq(self.node, p.node.name.as_str())
+            && is_some_eq(self.id, p.id)
+            && is_some_eq(self.serial, p.serial)
+            && is_some_eq(self.creation, p.creation);
+        matched
             .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!(self
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1))
+            .map(|_| p)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("pid()")
     }
 }
 
-impl<'a, P0, P1, P2> Pattern<'a> for (P0, P1, P2)
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output, P2::Output);
+/// A port whose fields are optionally constrained.
+#[derive(Debug, Clone, Default)]
+pub struct PortPattern {
+    pub node: Option<&'static str>,
+    pub id: Option<u32>,
+    pub creation: Option<u8>,
+}
+pub fn port() -> PortPattern {
+    PortPattern::default()
+}
+impl PortPattern {
+    pub fn node(mut self, node: &'static str) -> Self {
+        self.node = Some(node);
+        self
+    }
+    pub fn id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn creation(mut self, creation: u8) -> Self {
+        self.creation = Some(creation);
+        self
+    }
+}
+impl<'a> Pattern<'a> for PortPattern {
+    type Output = &'a Port;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &t.elements;
-        r#try!((e.len() == 3)
+        let p: &Port = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let matched = is_some_eq(self.node, p.node.name.as_str())
+            && is_some_eq(self.id, p.id)
+            && is_some_eq(self.creation, p.creation);
+        matched
             .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!(self
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!(self
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2))
+            .map(|_| p)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("port()")
     }
 }
 
-impl<'a, P0, P1, P2, P3> Pattern<'a> for (P0, P1, P2, P3)
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output, P2::Output, P3::Output);
+/// A reference whose fields are optionally constrained.
+#[derive(Debug, Clone, Default)]
+pub struct RefPattern {
+    pub node: Option<&'static str>,
+    pub id: Option<Vec<u32>>,
+    pub creation: Option<u8>,
+}
+pub fn reference() -> RefPattern {
+    RefPattern::default()
+}
+impl RefPattern {
+    pub fn node(mut self, node: &'static str) -> Self {
+        self.node = Some(node);
+        self
+    }
+    pub fn id(mut self, id: Vec<u32>) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn creation(mut self, creation: u8) -> Self {
+        self.creation = Some(creation);
+        self
+    }
+}
+impl<'a> Pattern<'a> for RefPattern {
+    type Output = &'a Reference;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &t.elements;
-        r#try!((e.len() == 4)
+        let r: &Reference = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let matched = is_some_eq(self.node, r.node.name.as_str())
+            && self.id.as_ref().map_or(true, |id| *id == r.id)
+            && is_some_eq(self.creation, r.creation);
+        matched
             .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!(self
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!(self
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!(self
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3))
+            .map(|_| r)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("reference()")
     }
 }
 
-impl<'a, P0, P1, P2, P3, P4> Pattern<'a> for (P0, P1, P2, P3, P4)
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
-{
-    type Output = (P0::Output, P1::Output, P2::Output, P3::Output, P4::Output);
+/// An external fun (`fun M:F/A`) whose fields are optionally constrained.
+#[derive(Debug, Clone, Default)]
+pub struct ExternalFunPattern {
+    pub module: Option<&'static str>,
+    pub function: Option<&'static str>,
+    pub arity: Option<u8>,
+}
+pub fn external_fun() -> ExternalFunPattern {
+    ExternalFunPattern::default()
+}
+impl ExternalFunPattern {
+    pub fn module(mut self, module: &'static str) -> Self {
+        self.module = Some(module);
+        self
+    }
+    pub fn function(mut self, function: &'static str) -> Self {
+        self.function = Some(function);
+        self
+    }
+    pub fn arity(mut self, arity: u8) -> Self {
+        self.arity = Some(arity);
+        self
+    }
+}
+impl<'a> Pattern<'a> for ExternalFunPattern {
+    type Output = &'a ExternalFun;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &t.elements;
-        r#try!((e.len() == 5)
+        let f: &ExternalFun = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let matched = is_some_eq(self.module, f.module.name.as_str())
+            && is_some_eq(self.function, f.function.name.as_str())
+            && is_some_eq(self.arity, f.arity);
+        matched
             .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!(self
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!(self
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!(self
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o4 = r#try!(self
-            .4
-            .try_match(&e[4])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3, o4))
+            .map(|_| f)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("fun()")
     }
 }
 
-impl<'a, P0, P1, P2, P3, P4, P5> Pattern<'a> for (P0, P1, P2, P3, P4, P5)
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
-    P5: Pattern<'a> + 'static,
-{
-    type Output = (
-        P0::Output,
-        P1::Output,
-        P2::Output,
-        P3::Output,
-        P4::Output,
-        P5::Output,
-    );
+/// An internal fun whose module and arity are optionally constrained.
+///
+/// Funs of the old representation have no arity, so they never match an arity constraint.
+#[derive(Debug, Clone, Default)]
+pub struct InternalFunPattern {
+    pub module: Option<&'static str>,
+    pub arity: Option<u8>,
+}
+pub fn internal_fun() -> InternalFunPattern {
+    InternalFunPattern::default()
+}
+impl InternalFunPattern {
+    pub fn module(mut self, module: &'static str) -> Self {
+        self.module = Some(module);
+        self
+    }
+    pub fn arity(mut self, arity: u8) -> Self {
+        self.arity = Some(arity);
+        self
+    }
+}
+impl<'a> Pattern<'a> for InternalFunPattern {
+    type Output = &'a InternalFun;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let t: &Tuple = r#try!(input.try_as_ref().ok_or_else(|| self.unmatched(input)));
-        let e = &t.elements;
-        r#try!((e.len() == 6)
+        let f: &InternalFun = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let (module, arity) = match *f {
+            InternalFun::Old { ref module, .. } => (module, None),
+            InternalFun::New {
+                ref module, arity, ..
+            } => (module, Some(arity)),
+        };
+        let matched = is_some_eq(self.module, module.name.as_str())
+            && self.arity.map_or(true, |x| Some(x) == arity);
+        matched
             .as_option()
-            .ok_or_else(|| self.unmatched(input)));
-        let o0 = r#try!(self
-            .0
-            .try_match(&e[0])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o1 = r#try!(self
-            .1
-            .try_match(&e[1])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o2 = r#try!(self
-            .2
-            .try_match(&e[2])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o3 = r#try!(self
-            .3
-            .try_match(&e[3])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o4 = r#try!(self
-            .4
-            .try_match(&e[4])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        let o5 = r#try!(self
-            .5
-            .try_match(&e[5])
-            .map_err(|e| self.unmatched(input).cause(e)));
-        Ok((o0, o1, o2, o3, o4, o5))
+            .map(|_| f)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("fun()")
     }
 }
 
-macro_rules! try_err {
-    ($e:expr) => {
-        match $e {
-            Ok(value) => return Ok(value),
-            Err(err) => err,
-        }
-    };
+/// A binary whose length in bytes is optionally bounded.
+///
+/// ```
+/// use eetf::Term;
+/// use eetf::pattern::binary;
+///
+/// let term: Term = "<<\"abc\">>".parse().unwrap();
+/// assert!(term.as_match(binary().min_len(1).max_len(3)).is_ok());
+/// assert!(term.as_match(binary().len(16)).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BinaryPattern {
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
 }
-
-#[derive(Debug, Clone)]
-pub struct Or<T>(pub T);
-impl<'a, P0, P1> Pattern<'a> for Or<(P0, P1)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-{
-    type Output = Union2<P0::Output, P1::Output>;
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let e = try_err!((self.0).0.try_match(input).map(|o| Union2::A(o)));
-        let e = try_err!((self.0).1.try_match(input).map(|o| Union2::B(o))).max_depth(e);
-        Err(self.unmatched(input).cause(e))
+pub fn binary() -> BinaryPattern {
+    BinaryPattern::default()
+}
+impl BinaryPattern {
+    pub fn min_len(mut self, n: usize) -> Self {
+        self.min_len = Some(n);
+        self
+    }
+    pub fn max_len(mut self, n: usize) -> Self {
+        self.max_len = Some(n);
+        self
+    }
+    pub fn len(self, n: usize) -> Self {
+        self.min_len(n).max_len(n)
     }
 }
-impl<'a, P0, P1, P2> Pattern<'a> for Or<(P0, P1, P2)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-{
-    type Output = Union3<P0::Output, P1::Output, P2::Output>;
+impl<'a> Pattern<'a> for BinaryPattern {
+    type Output = &'a Binary;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let e = try_err!((self.0).0.try_match(input).map(|o| Union3::A(o)));
-        let e = try_err!((self.0).1.try_match(input).map(|o| Union3::B(o))).max_depth(e);
-        let e = try_err!((self.0).2.try_match(input).map(|o| Union3::C(o))).max_depth(e);
-        Err(self.unmatched(input).cause(e))
+        let b: &Binary = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        in_bounds(b.bytes.len(), self.min_len, self.max_len)
+            .as_option()
+            .map(|_| b)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        match (self.min_len, self.max_len) {
+            (Some(min), Some(max)) if min == max => Spec::Bits {
+                size: min * 8,
+                unit: 0,
+            },
+            _ => Spec::Bits { size: 0, unit: 8 },
+        }
     }
 }
-impl<'a, P0, P1, P2, P3> Pattern<'a> for Or<(P0, P1, P2, P3)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-{
-    type Output = Union4<P0::Output, P1::Output, P2::Output, P3::Output>;
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let e = try_err!((self.0).0.try_match(input).map(|o| Union4::A(o)));
-        let e = try_err!((self.0).1.try_match(input).map(|o| Union4::B(o))).max_depth(e);
-        let e = try_err!((self.0).2.try_match(input).map(|o| Union4::C(o))).max_depth(e);
-        let e = try_err!((self.0).3.try_match(input).map(|o| Union4::D(o))).max_depth(e);
-        Err(self.unmatched(input).cause(e))
+
+/// A bitstring (whose size is not a multiple of 8) whose size in bits is optionally bounded.
+#[derive(Debug, Clone, Default)]
+pub struct BitBinaryPattern {
+    pub min_bits: Option<usize>,
+    pub max_bits: Option<usize>,
+}
+pub fn bit_binary() -> BitBinaryPattern {
+    BitBinaryPattern::default()
+}
+impl BitBinaryPattern {
+    pub fn min_bits(mut self, n: usize) -> Self {
+        self.min_bits = Some(n);
+        self
+    }
+    pub fn max_bits(mut self, n: usize) -> Self {
+        self.max_bits = Some(n);
+        self
+    }
+    pub fn bits(self, n: usize) -> Self {
+        self.min_bits(n).max_bits(n)
     }
 }
-impl<'a, P0, P1, P2, P3, P4> Pattern<'a> for Or<(P0, P1, P2, P3, P4)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
-{
-    type Output = Union5<P0::Output, P1::Output, P2::Output, P3::Output, P4::Output>;
+impl<'a> Pattern<'a> for BitBinaryPattern {
+    type Output = &'a BitBinary;
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let e = try_err!((self.0).0.try_match(input).map(|o| Union5::A(o)));
-        let e = try_err!((self.0).1.try_match(input).map(|o| Union5::B(o))).max_depth(e);
-        let e = try_err!((self.0).2.try_match(input).map(|o| Union5::C(o))).max_depth(e);
-        let e = try_err!((self.0).3.try_match(input).map(|o| Union5::D(o))).max_depth(e);
-        let e = try_err!((self.0).4.try_match(input).map(|o| Union5::E(o))).max_depth(e);
-        Err(self.unmatched(input).cause(e))
+        let b: &BitBinary = input.try_as_ref().ok_or_else(|| self.unmatched(input))?;
+        let bits = bit_len(b);
+        in_bounds(bits, self.min_bits, self.max_bits)
+            .as_option()
+            .map(|_| b)
+            .ok_or_else(|| self.unmatched(input))
+    }
+    fn spec(&self) -> Spec {
+        match (self.min_bits, self.max_bits) {
+            (Some(min), Some(max)) if min == max => Spec::Bits { size: min, unit: 0 },
+            _ => Spec::Bits { size: 0, unit: 1 },
+        }
     }
 }
-impl<'a, P0, P1, P2, P3, P4, P5> Pattern<'a> for Or<(P0, P1, P2, P3, P4, P5)>
-where
-    P0: Pattern<'a> + 'static,
-    P1: Pattern<'a> + 'static,
-    P2: Pattern<'a> + 'static,
-    P3: Pattern<'a> + 'static,
-    P4: Pattern<'a> + 'static,
-    P5: Pattern<'a> + 'static,
-{
-    type Output = Union6<P0::Output, P1::Output, P2::Output, P3::Output, P4::Output, P5::Output>;
-    fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
-        let e = try_err!((self.0).0.try_match(input).map(|o| Union6::A(o)));
-        let e = try_err!((self.0).1.try_match(input).map(|o| Union6::B(o))).max_depth(e);
-        let e = try_err!((self.0).2.try_match(input).map(|o| Union6::C(o))).max_depth(e);
-        let e = try_err!((self.0).3.try_match(input).map(|o| Union6::D(o))).max_depth(e);
-        let e = try_err!((self.0).4.try_match(input).map(|o| Union6::E(o))).max_depth(e);
-        let e = try_err!((self.0).5.try_match(input).map(|o| Union6::F(o))).max_depth(e);
-        Err(self.unmatched(input).cause(e))
+
+fn type_spec<T: 'static>() -> Spec {
+    use std::any::TypeId;
+
+    let id = TypeId::of::<T>();
+    if id == TypeId::of::<Atom>() {
+        Spec::Builtin("atom()")
+    } else if id == TypeId::of::<FixInteger>() {
+        bounded(i32::MIN, i32::MAX)
+    } else if id == TypeId::of::<BigInteger>() {
+        integer_spec(None, None)
+    } else if id == TypeId::of::<Float>() {
+        Spec::Builtin("float()")
+    } else if id == TypeId::of::<Pid>() {
+        Spec::Builtin("pid()")
+    } else if id == TypeId::of::<Port>() {
+        Spec::Builtin("port()")
+    } else if id == TypeId::of::<Reference>() {
+        Spec::Builtin("reference()")
+    } else if id == TypeId::of::<ExternalFun>() || id == TypeId::of::<InternalFun>() {
+        Spec::Builtin("fun()")
+    } else if id == TypeId::of::<Binary>() {
+        Spec::Bits { size: 0, unit: 8 }
+    } else if id == TypeId::of::<BitBinary>() {
+        Spec::Bits { size: 0, unit: 1 }
+    } else if id == TypeId::of::<List>() {
+        Spec::List(Box::new(Spec::Any))
+    } else if id == TypeId::of::<ImproperList>() {
+        Spec::Builtin("nonempty_improper_list(term(), term())")
+    } else if id == TypeId::of::<Tuple>() {
+        Spec::Builtin("tuple()")
+    } else if id == TypeId::of::<Map>() {
+        Spec::Builtin("map()")
+    } else {
+        Spec::Any
     }
 }
 
+fn integer_spec(min: Option<i128>, max: Option<i128>) -> Spec {
+    Spec::Integer { min, max }
+}
+
+// Integers match float patterns too.
+fn float_spec(_min: Option<i128>, _max: Option<i128>) -> Spec {
+    Spec::Builtin("number()")
+}
+
+fn bounded<T: Into<i128>>(min: T, max: T) -> Spec {
+    integer_spec(Some(min.into()), Some(max.into()))
+}
+
+fn is_some_eq<T: PartialEq>(expected: Option<T>, actual: T) -> bool {
+    expected.map_or(true, |x| x == actual)
+}
+
+fn in_bounds(n: usize, min: Option<usize>, max: Option<usize>) -> bool {
+    min.map_or(true, |min| min <= n) && max.map_or(true, |max| n <= max)
+}
+
 #[derive(Debug, Clone)]
 pub struct Ascii;
 impl<'a> Pattern<'a> for Ascii {
@@ -724,6 +2317,9 @@ impl<'a> Pattern<'a> for Ascii {
             Err(self.unmatched(input))
         }
     }
+    fn spec(&self) -> Spec {
+        integer_spec(Some(0), Some(127))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -734,6 +2330,9 @@ impl<'a> Pattern<'a> for Unicode {
         let n = r#try!(input.to_u32().ok_or_else(|| self.unmatched(input)));
         ::std::char::from_u32(n).ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        Spec::Builtin("char()")
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -755,6 +2354,9 @@ where
         }
         Ok(s)
     }
+    fn spec(&self) -> Spec {
+        Spec::List(Box::new(self.0.spec()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -764,6 +2366,9 @@ impl<'a> Pattern<'a> for U8 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_u8().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(u8::MIN, u8::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -773,6 +2378,9 @@ impl<'a> Pattern<'a> for I8 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_i8().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(i8::MIN, i8::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -782,6 +2390,9 @@ impl<'a> Pattern<'a> for U16 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_u16().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(u16::MIN, u16::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -791,6 +2402,9 @@ impl<'a> Pattern<'a> for I16 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_i16().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(i16::MIN, i16::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -800,6 +2414,9 @@ impl<'a> Pattern<'a> for U32 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_u32().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(u32::MIN, u32::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -809,6 +2426,9 @@ impl<'a> Pattern<'a> for I32 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_i32().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(i32::MIN, i32::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -818,6 +2438,9 @@ impl<'a> Pattern<'a> for U64 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_u64().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(u64::MIN, u64::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -827,6 +2450,9 @@ impl<'a> Pattern<'a> for I64 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_i64().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        bounded(i64::MIN, i64::MAX)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -836,6 +2462,9 @@ impl<'a> Pattern<'a> for Int {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_bigint().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        integer_spec(None, None)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -845,6 +2474,9 @@ impl<'a> Pattern<'a> for Uint {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_biguint().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        integer_spec(Some(0), None)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -854,6 +2486,9 @@ impl<'a> Pattern<'a> for F32 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_f32().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        float_spec(None, None)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -863,4 +2498,7 @@ impl<'a> Pattern<'a> for F64 {
     fn try_match(&self, input: &'a Term) -> Result<'a, Self::Output> {
         input.to_f64().ok_or_else(|| self.unmatched(input))
     }
+    fn spec(&self) -> Spec {
+        float_spec(None, None)
+    }
 }
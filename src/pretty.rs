@@ -0,0 +1,330 @@
+//! Pretty printer which lays out terms like `io:format("~p")`.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::Term;
+//!
+//! let term: Term = "{ok,[{name,<<\"alice\">>},{roles,[admin,developer]}]}".parse().unwrap();
+//! assert_eq!(
+//!     term.pretty().width(40).to_string(),
+//!     "{ok,[{name,<<\"alice\">>},\n     {roles,[admin,developer]}]}"
+//! );
+//! assert_eq!(term.pretty().depth(4).to_string(), "{ok,[{name,...},{roles,...}]}");
+//! ```
+use super::*;
+use std::fmt::Write;
+
+/// A term wrapper whose `Display` implementation pretty prints the term.
+///
+/// Compound terms which do not fit in the remaining width are broken into
+/// several lines, and each element is aligned with the first one.
+#[derive(Debug, Clone)]
+pub struct Pretty<'a> {
+    term: &'a Term,
+    width: usize,
+    depth: Option<usize>,
+    chars_limit: Option<usize>,
+}
+impl<'a> Pretty<'a> {
+    /// Makes a new pretty printer with the default line width (80 columns).
+    pub fn new(term: &'a Term) -> Self {
+        Pretty {
+            term,
+            width: 80,
+            depth: None,
+            chars_limit: None,
+        }
+    }
+
+    /// Sets the line width.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the depth limit, like the extra argument of `~P`.
+    ///
+    /// Sub-terms below the limit, and the elements of a tuple, list, map or binary
+    /// beyond it, are replaced with `...`.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Sets the maximum number of characters written; the output is cut and
+    /// suffixed with `...` if it is longer.
+    pub fn chars_limit(mut self, limit: usize) -> Self {
+        self.chars_limit = Some(limit);
+        self
+    }
+
+    fn layout(&self, out: &mut String, term: &Term, depth: Option<usize>, trailing: usize) {
+        let col = column(out);
+        let mut flat = Bounded::new(self.width.saturating_sub(col + trailing));
+        if write_flat(&mut flat, term, depth).is_ok() {
+            out.push_str(&flat.buf);
+            return;
+        }
+        match *term {
+            Term::Tuple(ref x) if !x.elements.is_empty() && is_expandable(depth) => {
+                self.layout_seq(out, ("{", "}"), &x.elements, None, depth, trailing)
+            }
+            Term::List(ref x)
+                if !x.elements.is_empty() && is_expandable(depth) && !x.is_printable() =>
+            {
+                self.layout_seq(out, ("[", "]"), &x.elements, None, depth, trailing)
+            }
+            Term::ImproperList(ref x) if is_expandable(depth) => {
+                self.layout_seq(out, ("[", "]"), &x.elements, Some(&x.last), depth, trailing)
+            }
+            Term::Map(ref x) if !x.entries.is_empty() && is_expandable(depth) => {
+                self.layout_map(out, x, depth, trailing)
+            }
+            _ => {
+                let _ = write_flat(out, term, depth);
+            }
+        }
+    }
+
+    fn layout_seq(
+        &self,
+        out: &mut String,
+        (open, close): (&str, &str),
+        elements: &[Term],
+        last: Option<&Term>,
+        depth: Option<usize>,
+        trailing: usize,
+    ) {
+        out.push_str(open);
+        let is_list = open == "[";
+        let child_depth = depth.map(|d| d - 1);
+        let mut iter = elements.iter().enumerate();
+
+        // Tagged tuples keep their tag on the first line and align the other
+        // elements after it, e.g. `{ok,[...` or `{state,a,\n       b}`.
+        match elements.first() {
+            Some(Term::Atom(tag)) if !is_list && elements.len() > 1 => {
+                let _ = syntax::write_atom(out, &tag.name);
+                out.push(',');
+                iter.next();
+            }
+            _ => {}
+        }
+        let indent = column(out);
+
+        // Lists of atomic terms are filled, as many elements per line as fit.
+        let fill = is_list && elements.iter().all(is_atomic);
+        let mut first = true;
+        for (i, e) in iter {
+            if is_truncated(depth, i) {
+                out.push_str(if is_list { "|..." } else { ",..." });
+                out.push_str(close);
+                return;
+            }
+            let is_last = i + 1 == elements.len() && last.is_none();
+            let trailing = if is_last { close.len() + trailing } else { 1 };
+            if !first {
+                out.push(',');
+                let fits = fill && {
+                    let avail = self.width.saturating_sub(column(out) + trailing);
+                    write_flat(&mut Bounded::new(avail), e, child_depth).is_ok()
+                };
+                if !fits {
+                    out.push('\n');
+                    push_indent(out, indent);
+                }
+            }
+            first = false;
+            self.layout(out, e, child_depth, trailing);
+        }
+        if let Some(last) = last {
+            out.push('|');
+            self.layout(out, last, child_depth, close.len() + trailing);
+        }
+        out.push_str(close);
+    }
+
+    fn layout_map(&self, out: &mut String, map: &Map, depth: Option<usize>, trailing: usize) {
+        out.push_str("#{");
+        let indent = column(out);
+        let child_depth = depth.map(|d| d - 1);
+        for (i, (k, v)) in map.entries.iter().enumerate() {
+            if is_truncated(depth, i) {
+                out.push_str(",...}");
+                return;
+            }
+            if i != 0 {
+                out.push_str(",\n");
+                push_indent(out, indent);
+            }
+            self.layout(out, k, child_depth, 4);
+            out.push_str(" => ");
+            let is_last = i + 1 == map.entries.len();
+            self.layout(out, v, child_depth, if is_last { 1 + trailing } else { 1 });
+        }
+        out.push('}');
+    }
+}
+impl<'a> fmt::Display for Pretty<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.layout(&mut out, self.term, self.depth, 0);
+        match self.chars_limit {
+            Some(limit) if out.chars().count() > limit => {
+                f.write_str(&out.chars().take(limit).collect::<String>())?;
+                f.write_str("...")
+            }
+            _ => f.write_str(&out),
+        }
+    }
+}
+
+/// Returns whether a term at the depth can show its elements rather than `...`.
+fn is_expandable(depth: Option<usize>) -> bool {
+    depth.map_or(true, |d| d > 1)
+}
+
+fn is_truncated(depth: Option<usize>, index: usize) -> bool {
+    depth.is_some_and(|d| index + 1 >= d)
+}
+
+fn column(out: &str) -> usize {
+    out.rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.extend(std::iter::repeat(' ').take(indent));
+}
+
+fn is_atomic(term: &Term) -> bool {
+    !matches!(
+        *term,
+        Term::Tuple(_) | Term::List(_) | Term::ImproperList(_) | Term::Map(_)
+    )
+}
+
+fn is_printable_binary(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && bytes.iter().all(|&b| syntax::is_printable(b as char))
+}
+
+/// Writes `term` on a single line.
+fn write_flat<W: Write>(w: &mut W, term: &Term, depth: Option<usize>) -> fmt::Result {
+    if depth == Some(0) {
+        return w.write_str("...");
+    }
+    let child_depth = depth.map(|d| d - 1);
+    match *term {
+        Term::Tuple(ref x) => {
+            w.write_char('{')?;
+            for (i, e) in x.elements.iter().enumerate() {
+                if i != 0 {
+                    w.write_char(',')?;
+                }
+                if is_truncated(depth, i) {
+                    w.write_str("...")?;
+                    break;
+                }
+                write_flat(w, e, child_depth)?;
+            }
+            w.write_char('}')
+        }
+        Term::List(ref x) if x.is_printable() => write!(w, "{}", x),
+        Term::List(ref x) => {
+            w.write_char('[')?;
+            write_flat_elements(w, &x.elements, depth)?;
+            w.write_char(']')
+        }
+        Term::ImproperList(ref x) => {
+            w.write_char('[')?;
+            if write_flat_elements(w, &x.elements, depth)? {
+                w.write_char('|')?;
+                write_flat(w, &x.last, child_depth)?;
+            }
+            w.write_char(']')
+        }
+        Term::Map(ref x) => {
+            w.write_str("#{")?;
+            for (i, (k, v)) in x.entries.iter().enumerate() {
+                if i != 0 {
+                    w.write_char(',')?;
+                }
+                if is_truncated(depth, i) {
+                    w.write_str("...")?;
+                    break;
+                }
+                write_flat(w, k, child_depth)?;
+                w.write_str(" => ")?;
+                write_flat(w, v, child_depth)?;
+            }
+            w.write_char('}')
+        }
+        Term::Binary(ref x) if is_printable_binary(&x.bytes) => {
+            w.write_str("<<")?;
+            syntax::write_quoted(w, x.bytes.iter().map(|&b| b as char), '"')?;
+            w.write_str(">>")
+        }
+        Term::Binary(ref x) => {
+            w.write_str("<<")?;
+            for (i, b) in x.bytes.iter().enumerate() {
+                if i != 0 {
+                    w.write_char(',')?;
+                }
+                if is_truncated(depth, i) {
+                    w.write_str("...")?;
+                    break;
+                }
+                write!(w, "{}", b)?;
+            }
+            w.write_str(">>")
+        }
+        _ => write!(w, "{}", term),
+    }
+}
+
+/// Writes list elements, returning `false` if they were truncated.
+fn write_flat_elements<W: Write>(
+    w: &mut W,
+    elements: &[Term],
+    depth: Option<usize>,
+) -> Result<bool, fmt::Error> {
+    for (i, e) in elements.iter().enumerate() {
+        if is_truncated(depth, i) {
+            w.write_str(if i == 0 { "..." } else { "|..." })?;
+            return Ok(false);
+        }
+        if i != 0 {
+            w.write_char(',')?;
+        }
+        write_flat(w, e, depth.map(|d| d - 1))?;
+    }
+    Ok(true)
+}
+
+/// A writer which fails as soon as more than `limit` characters are written.
+struct Bounded {
+    buf: String,
+    limit: usize,
+    len: usize,
+}
+impl Bounded {
+    fn new(limit: usize) -> Self {
+        Bounded {
+            buf: String::new(),
+            limit,
+            len: 0,
+        }
+    }
+}
+impl Write for Bounded {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.chars().count();
+        if self.len > self.limit {
+            return Err(fmt::Error);
+        }
+        self.buf.push_str(s);
+        Ok(())
+    }
+}
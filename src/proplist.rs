@@ -0,0 +1,177 @@
+//! Helpers for property lists (`[{Key, Value}]`) and Elixir keyword lists.
+//!
+//! The lookup functions follow the semantics of the `proplists` module:
+//! the first entry of a key wins, and a bare atom `Key` is short for `{Key, true}`.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::{List, Term};
+//! use eetf::convert::TryAsRef;
+//!
+//! let term: Term = "[{port,8080},verbose,{host,\"localhost\"},{port,8081}]".parse().unwrap();
+//! let list: &List = term.try_as_ref().unwrap();
+//! let props = list.as_proplist();
+//! assert_eq!(props.get("port").map(|v| v.to_string()), Some("8080".to_owned()));
+//! assert_eq!(props.get_all("port").len(), 2);
+//! assert!(props.get_bool("verbose"));
+//! assert!(props.get("timeout").is_none());
+//! assert_eq!(props.to_map().to_string(), "#{port=>8080,verbose=>true,host=>\"localhost\"}");
+//! ```
+use super::*;
+use std::sync::OnceLock;
+
+/// A key of a property list.
+pub trait Key {
+    /// Returns `true` if `term` is this key.
+    fn matches(&self, term: &Term) -> bool;
+
+    /// Returns the key as a term, if it is known.
+    fn to_term(&self) -> Option<Term> {
+        None
+    }
+}
+
+/// A string key matches the atom of that name.
+impl Key for &str {
+    fn matches(&self, term: &Term) -> bool {
+        match *term {
+            Term::Atom(ref x) => x.name == *self,
+            _ => false,
+        }
+    }
+    fn to_term(&self) -> Option<Term> {
+        Some(Term::from(Atom::from(*self)))
+    }
+}
+impl Key for Atom {
+    fn matches(&self, term: &Term) -> bool {
+        Key::matches(&self.name.as_str(), term)
+    }
+    fn to_term(&self) -> Option<Term> {
+        Some(Term::from((*self).clone()))
+    }
+}
+impl Key for &Atom {
+    fn matches(&self, term: &Term) -> bool {
+        Key::matches(&self.name.as_str(), term)
+    }
+    fn to_term(&self) -> Option<Term> {
+        Some(Term::from((*self).clone()))
+    }
+}
+impl Key for Term {
+    fn matches(&self, term: &Term) -> bool {
+        self == term
+    }
+    fn to_term(&self) -> Option<Term> {
+        Some(self.clone())
+    }
+}
+impl Key for &Term {
+    fn matches(&self, term: &Term) -> bool {
+        *self == term
+    }
+    fn to_term(&self) -> Option<Term> {
+        Some((*self).clone())
+    }
+}
+
+/// A read-only view of a list as a property list.
+///
+/// Elements which are neither atoms nor tuples are ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct Proplist<'a> {
+    list: &'a List,
+}
+impl<'a> Proplist<'a> {
+    /// Makes a new view of `list`.
+    pub fn new(list: &'a List) -> Self {
+        Proplist { list }
+    }
+
+    /// Returns the value of the first entry of `key`, like `proplists:get_value/2`.
+    ///
+    /// A bare atom yields `true`, and a tuple whose size is not 2 yields `None`.
+    pub fn get<K: Key>(&self, key: K) -> Option<&'a Term> {
+        self.entries()
+            .find(|&(k, _)| key.matches(k))
+            .and_then(|(_, v)| v)
+    }
+
+    /// Returns the values of all the entries of `key`, like `proplists:get_all_values/2`.
+    pub fn get_all<K: Key>(&self, key: K) -> Vec<&'a Term> {
+        self.entries()
+            .filter(|&(k, _)| key.matches(k))
+            .filter_map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Returns `true` if the value of the first entry of `key` is the atom `true`,
+    /// like `proplists:get_bool/2`.
+    pub fn get_bool<K: Key>(&self, key: K) -> bool {
+        match self.get(key) {
+            Some(Term::Atom(x)) => x.name == "true",
+            _ => false,
+        }
+    }
+
+    /// Returns the keys in the order of their first occurrence, without duplicates.
+    pub fn keys(&self) -> Vec<&'a Term> {
+        let mut keys: Vec<&'a Term> = Vec::new();
+        for (k, _) in self.entries() {
+            if !keys.contains(&k) {
+                keys.push(k);
+            }
+        }
+        keys
+    }
+
+    /// Converts the list into a map, like `proplists:to_map/1`.
+    ///
+    /// The first entry of each key takes precedence.
+    pub fn to_map(&self) -> Map {
+        let mut entries: Vec<(Term, Term)> = Vec::new();
+        for (k, v) in self.entries() {
+            if let Some(v) = v {
+                if !entries.iter().any(|(x, _)| x == k) {
+                    entries.push((k.clone(), v.clone()));
+                }
+            }
+        }
+        Map::from(entries)
+    }
+
+    /// Converts a map into a property list, like `proplists:from_map/1`.
+    pub fn from_map(map: &Map) -> List {
+        List::from(
+            map.entries
+                .iter()
+                .map(|(k, v)| Term::from(Tuple::from(vec![k.clone(), v.clone()])))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns the key and the value (if the entry has one) of each entry.
+    fn entries(&self) -> impl Iterator<Item = (&'a Term, Option<&'a Term>)> {
+        self.list.elements.iter().filter_map(|e| match *e {
+            Term::Atom(_) => Some((e, Some(true_atom()))),
+            Term::Tuple(ref x) => match x.elements.len() {
+                0 => None,
+                2 => Some((&x.elements[0], Some(&x.elements[1]))),
+                _ => Some((&x.elements[0], None)),
+            },
+            _ => None,
+        })
+    }
+}
+impl<'a> From<&'a List> for Proplist<'a> {
+    fn from(list: &'a List) -> Self {
+        Proplist::new(list)
+    }
+}
+
+fn true_atom() -> &'static Term {
+    static TRUE: OnceLock<Term> = OnceLock::new();
+    TRUE.get_or_init(|| Term::from(Atom::from("true")))
+}
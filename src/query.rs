@@ -0,0 +1,354 @@
+//! Selection of sub-terms by query strings, like `.users[*].#name`.
+//!
+//! While the `pattern` module checks the whole shape of a term, a [`Query`] picks the
+//! sub-terms at a path, ignoring the terms which do not have it. It is handy for ad-hoc
+//! inspection of large terms.
+//!
+//! # Syntax
+//!
+//! A query is a sequence of the following selectors, which is applied to each term
+//! selected so far. The empty query selects the term itself.
+//!
+//! | Selector            | Selects                                                       |
+//! |---------------------|---------------------------------------------------------------|
+//! | `.N`, `[N]`         | The `N`-th (zero-based) element of a tuple or a list, counted from the end if `N` is negative |
+//! | `[A..B]`            | The `A`-th to (`B`-1)-th elements (`A` and `B` are optional)  |
+//! | `.*`, `[*]`         | All the elements of a tuple or a list, or all the values of a map |
+//! | `.name`, `.'Name'`  | The value of the atom key                                     |
+//! | `.#name`            | The value of the binary key `<<"name">>`                      |
+//! | `#{Key}`            | The value of the key written in the Erlang syntax, e.g. `#{<<"id">>}` |
+//!
+//! Keys are looked up in maps and in property lists (lists of `{Key, Value}` tuples,
+//! where the first entry of the key is selected).
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::Term;
+//!
+//! let term: Term = r#"{ok,#{users => [#{<<"name">> => <<"a">>},#{<<"name">> => <<"b">>}]}}"#
+//!     .parse()
+//!     .unwrap();
+//! let names = term.query(".1.users[*].#name").unwrap();
+//! assert_eq!(names, [&"<<\"a\">>".parse().unwrap(), &"<<\"b\">>".parse().unwrap()]);
+//!
+//! let mut term: Term = "{[1,2,3],x}".parse().unwrap();
+//! for x in term.query_mut(".0[1..]").unwrap() {
+//!     *x = Term::from(eetf::Atom::from("y"));
+//! }
+//! assert_eq!(term, "{[1,y,y],x}".parse().unwrap());
+//! ```
+use super::*;
+
+/// A selector of sub-terms (see the module documentation).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selector {
+    /// The element at the index, counted from the end if negative.
+    Index(isize),
+
+    /// The elements in the range, whose bounds are counted from the end if negative.
+    Range(Option<isize>, Option<isize>),
+
+    /// All the elements or map values.
+    All,
+
+    /// The value of the key.
+    Key(Term),
+}
+
+/// A parsed query, which is a sequence of selectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query(pub Vec<Selector>);
+impl Query {
+    /// Returns the sub-terms of `term` selected by the query.
+    pub fn select<'a>(&self, term: &'a Term) -> Vec<&'a Term> {
+        let mut terms = vec![term];
+        for selector in &self.0 {
+            terms = terms
+                .into_iter()
+                .flat_map(|term| {
+                    let elements = elements(term);
+                    children(term, selector)
+                        .into_iter()
+                        .filter_map(move |child| match child {
+                            Child::At(i) => Some(elements[i]),
+                            Child::Prop(i) => prop_value(elements[i]),
+                        })
+                })
+                .collect();
+        }
+        terms
+    }
+
+    /// Returns mutable references to the sub-terms of `term` selected by the query.
+    pub fn select_mut<'a>(&self, term: &'a mut Term) -> Vec<&'a mut Term> {
+        let mut terms = vec![term];
+        for selector in &self.0 {
+            terms = terms
+                .into_iter()
+                .flat_map(|term| {
+                    let children = children(term, selector);
+                    let mut elements: Vec<_> = elements_mut(term).into_iter().map(Some).collect();
+                    children
+                        .into_iter()
+                        .filter_map(|child| match child {
+                            Child::At(i) => elements[i].take(),
+                            Child::Prop(i) => elements[i].take().and_then(prop_value_mut),
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+        terms
+    }
+}
+impl str::FromStr for Query {
+    type Err = ParseError;
+
+    /// Parses a query (see the module documentation).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QueryParser { input: s, pos: 0 }.parse()
+    }
+}
+
+/// A sub-term, which is an element (or a map value) or the value of a property list entry.
+enum Child {
+    At(usize),
+    Prop(usize),
+}
+
+fn children(term: &Term, selector: &Selector) -> Vec<Child> {
+    let len = elements(term).len();
+    match *selector {
+        Selector::Index(i) => resolve(i, len)
+            .filter(|&i| i < len)
+            .map(Child::At)
+            .into_iter()
+            .collect(),
+        Selector::Range(start, end) => {
+            let start = start.map_or(0, |i| resolve(i, len).unwrap_or(0).min(len));
+            let end = end.map_or(len, |i| resolve(i, len).unwrap_or(0).min(len));
+            (start..end.max(start)).map(Child::At).collect()
+        }
+        Selector::All => (0..len).map(Child::At).collect(),
+        Selector::Key(ref key) => match *term {
+            Term::Map(ref x) => x.entries.iter().position(|(k, _)| k == key).map(Child::At),
+            Term::List(ref x) => x
+                .elements
+                .iter()
+                .position(|e| prop_key(e) == Some(key))
+                .map(Child::Prop),
+            _ => None,
+        }
+        .into_iter()
+        .collect(),
+    }
+}
+
+/// Converts an index which may be counted from the end into one from the start.
+fn resolve(i: isize, len: usize) -> Option<usize> {
+    if i < 0 {
+        len.checked_sub(i.unsigned_abs())
+    } else {
+        Some(i as usize)
+    }
+}
+
+fn elements(term: &Term) -> Vec<&Term> {
+    match *term {
+        Term::Tuple(ref x) => x.elements.iter().collect(),
+        Term::List(ref x) => x.elements.iter().collect(),
+        Term::ImproperList(ref x) => x.elements.iter().collect(),
+        Term::Map(ref x) => x.entries.iter().map(|(_, v)| v).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn elements_mut(term: &mut Term) -> Vec<&mut Term> {
+    match *term {
+        Term::Tuple(ref mut x) => x.elements.iter_mut().collect(),
+        Term::List(ref mut x) => x.elements.iter_mut().collect(),
+        Term::ImproperList(ref mut x) => x.elements.iter_mut().collect(),
+        Term::Map(ref mut x) => x.entries.iter_mut().map(|(_, v)| v).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn prop_key(term: &Term) -> Option<&Term> {
+    match *term {
+        Term::Tuple(ref x) if x.elements.len() == 2 => Some(&x.elements[0]),
+        _ => None,
+    }
+}
+
+fn prop_value(term: &Term) -> Option<&Term> {
+    match *term {
+        Term::Tuple(ref x) if x.elements.len() == 2 => Some(&x.elements[1]),
+        _ => None,
+    }
+}
+
+fn prop_value_mut(term: &mut Term) -> Option<&mut Term> {
+    match *term {
+        Term::Tuple(ref mut x) if x.elements.len() == 2 => Some(&mut x.elements[1]),
+        _ => None,
+    }
+}
+
+struct QueryParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+impl<'a> QueryParser<'a> {
+    fn parse(mut self) -> Result<Query, ParseError> {
+        let mut selectors = Vec::new();
+        self.skip_whitespace();
+        while let Some(c) = self.bump() {
+            let selector = match c {
+                '.' => self.parse_field()?,
+                '[' => self.parse_bracket()?,
+                '#' => self.parse_map_key()?,
+                c => return Err(self.unexpected_before(c)),
+            };
+            selectors.push(selector);
+            self.skip_whitespace();
+        }
+        Ok(Query(selectors))
+    }
+
+    /// Parses a selector after `.`.
+    fn parse_field(&mut self) -> Result<Selector, ParseError> {
+        match self.peek() {
+            Some('*') => {
+                self.bump();
+                Ok(Selector::All)
+            }
+            Some('#') => {
+                self.bump();
+                let name = self.parse_name()?;
+                Ok(Selector::Key(Term::from(Binary::from(name.as_bytes()))))
+            }
+            Some('\'') => Ok(Selector::Key(self.parse_term()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Selector::Index(self.parse_int()?)),
+            _ => {
+                let name = self.parse_name()?;
+                Ok(Selector::Key(Term::from(Atom::from(name))))
+            }
+        }
+    }
+
+    /// Parses a selector after `[`.
+    fn parse_bracket(&mut self) -> Result<Selector, ParseError> {
+        self.skip_whitespace();
+        let selector = if self.eat('*') {
+            Selector::All
+        } else {
+            let start = self.parse_opt_int()?;
+            self.skip_whitespace();
+            if self.rest().starts_with("..") {
+                self.pos += 2;
+                self.skip_whitespace();
+                Selector::Range(start, self.parse_opt_int()?)
+            } else {
+                match start {
+                    Some(i) => Selector::Index(i),
+                    None => return Err(self.unexpected_here()),
+                }
+            }
+        };
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(selector)
+    }
+
+    /// Parses a selector after `#`.
+    fn parse_map_key(&mut self) -> Result<Selector, ParseError> {
+        self.expect('{')?;
+        let key = self.parse_term()?;
+        self.skip_whitespace();
+        self.expect('}')?;
+        Ok(Selector::Key(key))
+    }
+
+    fn parse_term(&mut self) -> Result<Term, ParseError> {
+        let (term, pos) = parse::Parser::parse_term_at(self.input, self.pos)?;
+        self.pos = pos;
+        Ok(term)
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(syntax::is_atom_char) {
+            self.bump();
+        }
+        if start == self.pos {
+            return Err(self.unexpected_here());
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn parse_opt_int(&mut self) -> Result<Option<isize>, ParseError> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_int().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<isize, ParseError> {
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map_err(|_| ParseError::InvalidNumber { position: start })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected_here())
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+    fn unexpected_here(&self) -> ParseError {
+        match self.peek() {
+            Some(found) => ParseError::UnexpectedChar {
+                position: self.pos,
+                found,
+            },
+            None => ParseError::UnexpectedEof,
+        }
+    }
+    fn unexpected_before(&self, found: char) -> ParseError {
+        ParseError::UnexpectedChar {
+            position: self.pos - found.len_utf8(),
+            found,
+        }
+    }
+}
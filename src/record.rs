@@ -0,0 +1,388 @@
+//! Erlang records.
+//!
+//! A record `#user{id, name}` is a tuple `{user, Id, Name}` at runtime.
+//! A [`Record`] descriptor gives names to the elements of such tuples.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::{Term, Tuple};
+//! use eetf::convert::TryAsRef;
+//! use eetf::record::Record;
+//!
+//! let records = Record::parse_hrl(r#"
+//!     -record(user, {id :: integer(), name = <<"anonymous">> :: binary(), email}).
+//! "#).unwrap();
+//! let user = &records[0];
+//!
+//! let term: Term = "{user,1,<<\"alice\">>,undefined}".parse().unwrap();
+//! let tuple: &Tuple = term.try_as_ref().unwrap();
+//! let view = user.view(tuple).unwrap();
+//! assert_eq!(view.get_into::<u32>("id").unwrap(), 1);
+//! assert_eq!(view.get_into::<String>("name").unwrap(), "alice");
+//!
+//! let tuple = user.build(vec![("id", 2)]).unwrap();
+//! let expected: Term = "{user,2,<<\"anonymous\">>,undefined}".parse().unwrap();
+//! assert_eq!(Term::from(tuple), expected);
+//! ```
+use super::*;
+use crate::convert::{FromTerm, TryAsRef};
+use std::error;
+
+/// Errors which can occur when parsing record definitions or accessing records
+#[derive(Debug)]
+pub enum RecordError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    Mismatch {
+        record: String,
+        value: Box<Term>,
+    },
+    UnknownField {
+        record: String,
+        field: String,
+    },
+    Field {
+        field: String,
+        error: Box<DecodeError>,
+    },
+}
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecordError::Syntax { line, ref message } => write!(f, "{} at line {}", message, line),
+            RecordError::Mismatch {
+                ref record,
+                ref value,
+            } => write!(f, "{} is not a #{}{{}} record", value, record),
+            RecordError::UnknownField {
+                ref record,
+                ref field,
+            } => write!(f, "#{}{{}} has no field {}", record, field),
+            RecordError::Field {
+                ref field,
+                ref error,
+            } => write!(f, "Field {}: {}", field, error),
+        }
+    }
+}
+impl error::Error for RecordError {
+    fn description(&self) -> &str {
+        match *self {
+            RecordError::Syntax { .. } => "Invalid record definition",
+            RecordError::Mismatch { .. } => "Term is not the expected record",
+            RecordError::UnknownField { .. } => "Unknown record field",
+            RecordError::Field { .. } => "Unexpected field value",
+        }
+    }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RecordError::Field { ref error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+/// A field of a record.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Field {
+    pub name: String,
+
+    /// The default value, which is `undefined` if it is `None`.
+    pub default: Option<Term>,
+}
+
+/// A record definition.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+impl Record {
+    /// Makes a new record definition without fields.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Record {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a field without a default value.
+    pub fn field<N: Into<String>>(mut self, name: N) -> Self {
+        self.fields.push(Field {
+            name: name.into(),
+            default: None,
+        });
+        self
+    }
+
+    /// Adds a field with a default value.
+    pub fn field_with_default<N: Into<String>>(mut self, name: N, default: Term) -> Self {
+        self.fields.push(Field {
+            name: name.into(),
+            default: Some(default),
+        });
+        self
+    }
+
+    /// Parses the `-record(...)` declarations in the source of an `.hrl` file.
+    ///
+    /// Everything else in the source is ignored, as are type annotations
+    /// and default values which are not literal terms (e.g. function calls or macros).
+    pub fn parse_hrl(source: &str) -> Result<Vec<Record>, RecordError> {
+        let source = strip_comments(source);
+        let mut records = Vec::new();
+        let mut rest = 0;
+        while let Some(i) = source[rest..].find("-record") {
+            let start = rest + i + "-record".len();
+            let line = source[..start].matches('\n').count() + 1;
+            let syntax_error = |message: &str| RecordError::Syntax {
+                line,
+                message: message.to_owned(),
+            };
+            let open = match source[start..].trim_start().strip_prefix('(') {
+                Some(x) => source.len() - x.len(),
+                None => return Err(syntax_error("Expected '(' after -record")),
+            };
+            let close = match find_top_level(&source[open..], |s| s.starts_with(')')) {
+                Some(x) => open + x,
+                None => return Err(syntax_error("Unterminated record declaration")),
+            };
+            records.push(parse_declaration(&source[open..close]).map_err(syntax_error)?);
+            rest = close;
+        }
+        Ok(records)
+    }
+
+    /// Returns the position of `field` in the record (starting from 0, without the name).
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|x| x.name == field)
+    }
+
+    /// Makes a view of `tuple`, which must be a record of this definition.
+    pub fn view<'a>(&'a self, tuple: &'a Tuple) -> Result<RecordView<'a>, RecordError> {
+        let matched = tuple.elements.len() == self.fields.len() + 1
+            && match tuple.elements[0] {
+                Term::Atom(ref x) => x.name == self.name,
+                _ => false,
+            };
+        if matched {
+            Ok(RecordView {
+                record: self,
+                tuple,
+            })
+        } else {
+            Err(RecordError::Mismatch {
+                record: self.name.clone(),
+                value: Box::new(Term::from(tuple.clone())),
+            })
+        }
+    }
+
+    /// Builds a record from field/value pairs.
+    ///
+    /// Missing fields are set to their default values, or `undefined`.
+    pub fn build<I, K, V>(&self, values: I) -> Result<Tuple, RecordError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<Term>,
+    {
+        let mut elements = vec![None; self.fields.len()];
+        for (k, v) in values {
+            match self.field_index(k.as_ref()) {
+                Some(i) => elements[i] = Some(v.into()),
+                None => {
+                    return Err(RecordError::UnknownField {
+                        record: self.name.clone(),
+                        field: k.as_ref().to_owned(),
+                    })
+                }
+            }
+        }
+        let elements = elements.into_iter().zip(&self.fields).map(|(x, field)| {
+            x.or_else(|| field.default.clone())
+                .unwrap_or_else(|| Term::from(Atom::from("undefined")))
+        });
+        Ok(Tuple::from(
+            std::iter::once(Term::from(Atom::from(self.name.as_str())))
+                .chain(elements)
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// A view of a record tuple with named fields.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordView<'a> {
+    record: &'a Record,
+    tuple: &'a Tuple,
+}
+impl<'a> RecordView<'a> {
+    /// Returns the record definition.
+    pub fn record(&self) -> &'a Record {
+        self.record
+    }
+
+    /// Returns the value of `field`, or `None` if there is no such field.
+    pub fn get(&self, field: &str) -> Option<&'a Term> {
+        self.record
+            .field_index(field)
+            .map(|i| &self.tuple.elements[i + 1])
+    }
+
+    /// Returns a reference to the value of `field` as a specific term type.
+    pub fn get_as<T>(&self, field: &str) -> Result<&'a T, RecordError>
+    where
+        Term: TryAsRef<T>,
+    {
+        let value = self.get_field(field)?;
+        value.try_as_ref().ok_or_else(|| RecordError::Field {
+            field: field.to_owned(),
+            error: Box::new(DecodeError::UnexpectedType {
+                value: value.clone(),
+                expected: short_type_name::<T>().to_owned(),
+            }),
+        })
+    }
+
+    /// Converts the value of `field` into a Rust value.
+    pub fn get_into<T: FromTerm>(&self, field: &str) -> Result<T, RecordError> {
+        T::from_term(self.get_field(field)?.clone()).map_err(|error| RecordError::Field {
+            field: field.to_owned(),
+            error: Box::new(error),
+        })
+    }
+
+    /// Returns the names and values of the fields.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a Term)> {
+        self.record
+            .fields
+            .iter()
+            .map(|x| x.name.as_str())
+            .zip(&self.tuple.elements[1..])
+    }
+
+    fn get_field(&self, field: &str) -> Result<&'a Term, RecordError> {
+        self.get(field).ok_or_else(|| RecordError::UnknownField {
+            record: self.record.name.clone(),
+            field: field.to_owned(),
+        })
+    }
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Parses `name, {field = Default :: type(), ...}`.
+fn parse_declaration(s: &str) -> Result<Record, &'static str> {
+    let comma =
+        find_top_level(s, |s| s.starts_with(',')).ok_or("Expected ',' after record name")?;
+    let name = parse_atom(&s[..comma]).ok_or("Invalid record name")?;
+    let fields = s[comma + 1..].trim();
+    let fields = fields
+        .strip_prefix('{')
+        .and_then(|x| x.strip_suffix('}'))
+        .ok_or("Expected a tuple of fields")?;
+
+    let mut record = Record::new(name);
+    let mut rest = fields;
+    while !rest.trim().is_empty() {
+        let end = find_top_level(rest, |s| s.starts_with(',')).unwrap_or(rest.len());
+        let field = &rest[..end];
+        let field = match find_top_level(field, |s| s.starts_with("::")) {
+            Some(i) => &field[..i],
+            None => field,
+        };
+        let (name, default) = match find_top_level(field, is_match_operator) {
+            Some(i) => (&field[..i], field[i + 1..].trim().parse().ok()),
+            None => (field, None),
+        };
+        record.fields.push(Field {
+            name: parse_atom(name).ok_or("Invalid field name")?,
+            default,
+        });
+        rest = rest.get(end + 1..).unwrap_or("");
+    }
+    Ok(record)
+}
+
+fn parse_atom(s: &str) -> Option<String> {
+    match s.trim().parse() {
+        Ok(Term::Atom(x)) => Some(x.name),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `s` starts with `=` but not with `==`, `=:=`, `=/=`, `=<` or `=>`.
+fn is_match_operator(s: &str) -> bool {
+    s.starts_with('=') && !s[1..].starts_with(['=', ':', '/', '<', '>'])
+}
+
+/// Returns the position of the first match of `pred` which is outside of brackets,
+/// strings and character literals.
+fn find_top_level<F>(s: &str, pred: F) -> Option<usize>
+where
+    F: Fn(&str) -> bool,
+{
+    find_code(s, |rest, depth| depth == 0 && pred(rest))
+}
+
+/// Returns the position of the first match of `pred` which is outside of strings and
+/// character literals. `pred` is given the rest of `s` and the nesting depth of brackets.
+fn find_code<F>(s: &str, pred: F) -> Option<usize>
+where
+    F: Fn(&str, usize) -> bool,
+{
+    let mut depth = 0;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        let rest = &s[i..];
+        if pred(rest, depth) {
+            return Some(i);
+        }
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' => depth = depth.saturating_sub(1),
+            '<' if rest.starts_with("<<") => {
+                chars.next();
+                depth += 1;
+            }
+            '>' if rest.starts_with(">>") && depth > 0 => {
+                chars.next();
+                depth -= 1;
+            }
+            '"' | '\'' => {
+                while let Some((_, x)) = chars.next() {
+                    if x == '\\' {
+                        chars.next();
+                    } else if x == c {
+                        break;
+                    }
+                }
+            }
+            '$' => {
+                if let Some((_, '\\')) = chars.next() {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes `%` comments, keeping the line breaks.
+fn strip_comments(s: &str) -> String {
+    s.lines()
+        .map(|line| match find_code(line, |s, _| s.starts_with('%')) {
+            Some(i) => &line[..i],
+            None => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,540 @@
+//! Serialization of Rust values into terms with [serde](https://serde.rs).
+//!
+//! This module is available if the `serde` feature is enabled.
+//!
+//! # Mapping
+//!
+//! | Rust                                  | Erlang                                         |
+//! |---------------------------------------|------------------------------------------------|
+//! | `bool`                                | `true` / `false`                               |
+//! | integers                              | integer                                        |
+//! | `f32`, `f64`                          | float (non-finite values are an error)         |
+//! | `char`                                | integer (the code point)                       |
+//! | `String`, `&str`                      | binary (UTF-8)                                 |
+//! | bytes (e.g. `serde_bytes`)            | binary                                         |
+//! | `None`                                | `undefined` (or `nil`, see `NoneRepr`)         |
+//! | `Some(v)`, `struct S(v)`              | `v`                                            |
+//! | `()`                                  | `{}`                                           |
+//! | sequences                             | list                                           |
+//! | tuples, `struct S(a, b)`              | tuple                                          |
+//! | maps                                  | map                                            |
+//! | `struct S { a, b }`                   | `#{a => A, b => B}` (or `{'S', A, B}` or `%S{a: A, b: B}`, see `StructRepr`) |
+//! | `E::V`                                | `'V'`                                          |
+//! | `E::V(a)`, `E::V(a, b)`               | `{'V', A}`, `{'V', A, B}`                      |
+//! | `E::V { a, b }`                       | `{'V', #{a => A, b => B}}` (or `{'V', A, B}`)  |
+//!
+//! Names are used as they are, so `#[serde(rename = "...")]` or
+//! `#[serde(rename_all = "snake_case")]` can be used to get the usual Erlang atoms.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::ser::{self, Serializer, StructRepr};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! #[serde(rename = "user")]
+//! struct User {
+//!     id: u32,
+//!     name: String,
+//!     email: Option<String>,
+//! }
+//!
+//! let user = User { id: 1, name: "alice".to_owned(), email: None };
+//! assert_eq!(
+//!     ser::to_term(&user).unwrap().to_string(),
+//!     "#{id=>1,name=><<97,108,105,99,101>>,email=>undefined}"
+//! );
+//!
+//! let serializer = Serializer::new().struct_repr(StructRepr::Record);
+//! assert_eq!(
+//!     user.serialize(serializer).unwrap().to_string(),
+//!     "{user,1,<<97,108,105,99,101>>,undefined}"
+//! );
+//! ```
+use super::*;
+use num::bigint::Sign;
+use serde::ser::{self, Serialize};
+use std::error;
+
+/// Errors which can occur when serializing a value
+#[derive(Debug)]
+pub enum Error {
+    Encode(EncodeError),
+    NonFiniteFloat(f64),
+    Custom(String),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(ref x) => x.fmt(f),
+            Error::NonFiniteFloat(x) => write!(f, "Tried to serialize non-finite float {}", x),
+            Error::Custom(ref x) => x.fmt(f),
+        }
+    }
+}
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Encode(_) => "Cannot encode the serialized term",
+            Error::NonFiniteFloat(_) => "Non-finite float is not supported",
+            Error::Custom(ref x) => x,
+        }
+    }
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref x) => Some(x),
+            _ => None,
+        }
+    }
+}
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+impl From<EncodeError> for Error {
+    fn from(err: EncodeError) -> Self {
+        Error::Encode(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// How structs (and struct variants) are represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructRepr {
+    /// A map whose keys are the field names as atoms (the default).
+    Map,
+    /// A record, i.e. a tuple tagged with the struct name followed by the field values.
+    Record,
+    /// An Elixir struct, i.e. a map with a `__struct__` key whose value is the struct name
+    /// prefixed by `Elixir.` (struct variants are represented as with `Map`).
+    ElixirStruct,
+}
+
+/// How `None` is represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoneRepr {
+    /// The `undefined` atom (the default).
+    Undefined,
+    /// The `nil` atom, as used by Elixir.
+    Nil,
+}
+
+/// Serializes the value into a term with the default settings.
+pub fn to_term<T: ?Sized + Serialize>(value: &T) -> Result<Term> {
+    value.serialize(Serializer::new())
+}
+
+/// Serializes the value and encodes it into `writer`.
+pub fn to_writer<W: io::Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<()> {
+    to_term(value)?.encode(writer)?;
+    Ok(())
+}
+
+/// Serializes the value and encodes it into a byte vector.
+pub fn to_vec<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// A serializer which produces `Term` values.
+#[derive(Debug, Clone, Copy)]
+pub struct Serializer {
+    struct_repr: StructRepr,
+    none_repr: NoneRepr,
+}
+impl Serializer {
+    /// Makes a new serializer with the default settings.
+    pub fn new() -> Self {
+        Serializer {
+            struct_repr: StructRepr::Map,
+            none_repr: NoneRepr::Undefined,
+        }
+    }
+
+    /// Sets the representation of structs.
+    pub fn struct_repr(mut self, repr: StructRepr) -> Self {
+        self.struct_repr = repr;
+        self
+    }
+
+    /// Sets the representation of `None`.
+    pub fn none_repr(mut self, repr: NoneRepr) -> Self {
+        self.none_repr = repr;
+        self
+    }
+}
+impl Default for Serializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn atom(name: &str) -> Term {
+    Term::from(Atom::from(name))
+}
+
+fn signed(value: i128) -> Term {
+    match i32::try_from(value) {
+        Ok(x) => Term::from(FixInteger::from(x)),
+        Err(_) => Term::from(BigInteger {
+            value: BigInt::from_signed_bytes_le(&value.to_le_bytes()),
+        }),
+    }
+}
+
+fn unsigned(value: u128) -> Term {
+    match i32::try_from(value) {
+        Ok(x) => Term::from(FixInteger::from(x)),
+        Err(_) => Term::from(BigInteger {
+            value: BigInt::from_bytes_le(Sign::Plus, &value.to_le_bytes()),
+        }),
+    }
+}
+
+fn tagged(tag: &str, mut elements: Vec<Term>) -> Term {
+    elements.insert(0, atom(tag));
+    Term::from(Tuple::from(elements))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Term;
+    type Error = Error;
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVec;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeStruct;
+
+    fn serialize_bool(self, v: bool) -> Result<Term> {
+        Ok(atom(if v { "true" } else { "false" }))
+    }
+    fn serialize_i8(self, v: i8) -> Result<Term> {
+        Ok(signed(v.into()))
+    }
+    fn serialize_i16(self, v: i16) -> Result<Term> {
+        Ok(signed(v.into()))
+    }
+    fn serialize_i32(self, v: i32) -> Result<Term> {
+        Ok(signed(v.into()))
+    }
+    fn serialize_i64(self, v: i64) -> Result<Term> {
+        Ok(signed(v.into()))
+    }
+    fn serialize_i128(self, v: i128) -> Result<Term> {
+        Ok(signed(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<Term> {
+        Ok(unsigned(v.into()))
+    }
+    fn serialize_u16(self, v: u16) -> Result<Term> {
+        Ok(unsigned(v.into()))
+    }
+    fn serialize_u32(self, v: u32) -> Result<Term> {
+        Ok(unsigned(v.into()))
+    }
+    fn serialize_u64(self, v: u64) -> Result<Term> {
+        Ok(unsigned(v.into()))
+    }
+    fn serialize_u128(self, v: u128) -> Result<Term> {
+        Ok(unsigned(v))
+    }
+    fn serialize_f32(self, v: f32) -> Result<Term> {
+        self.serialize_f64(v.into())
+    }
+    fn serialize_f64(self, v: f64) -> Result<Term> {
+        Float::try_from(v)
+            .map(Term::from)
+            .map_err(|_| Error::NonFiniteFloat(v))
+    }
+    fn serialize_char(self, v: char) -> Result<Term> {
+        Ok(unsigned(u128::from(v as u32)))
+    }
+    fn serialize_str(self, v: &str) -> Result<Term> {
+        Ok(Term::from(Binary::from(v.as_bytes())))
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Term> {
+        Ok(Term::from(Binary::from(v)))
+    }
+    fn serialize_none(self) -> Result<Term> {
+        Ok(atom(match self.none_repr {
+            NoneRepr::Undefined => "undefined",
+            NoneRepr::Nil => "nil",
+        }))
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Term> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Term> {
+        Ok(Term::from(Tuple::nil()))
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Term> {
+        match self.struct_repr {
+            StructRepr::Map => Ok(Term::from(Map::from(Vec::new()))),
+            StructRepr::Record => Ok(tagged(name, Vec::new())),
+            StructRepr::ElixirStruct => Ok(Term::from(elixir::StructBuilder::new(name).build())),
+        }
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Term> {
+        Ok(atom(variant))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Term> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Term> {
+        Ok(tagged(variant, vec![value.serialize(self)?]))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec> {
+        Ok(SerializeVec::new(self, Kind::List, len))
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec> {
+        Ok(SerializeVec::new(self, Kind::Tuple, Some(len)))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeVec> {
+        self.serialize_tuple(len)
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec> {
+        let mut seq = SerializeVec::new(self, Kind::Tuple, Some(len + 1));
+        seq.elements.push(atom(variant));
+        Ok(seq)
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            serializer: self,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeStruct> {
+        Ok(SerializeStruct::new(self, name, None, len))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStruct> {
+        Ok(SerializeStruct::new(self, variant, Some(variant), len))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    List,
+    Tuple,
+}
+
+/// Serializer of lists and tuples.
+#[doc(hidden)]
+pub struct SerializeVec {
+    serializer: Serializer,
+    kind: Kind,
+    elements: Vec<Term>,
+}
+impl SerializeVec {
+    fn new(serializer: Serializer, kind: Kind, len: Option<usize>) -> Self {
+        SerializeVec {
+            serializer,
+            kind,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        }
+    }
+
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.elements.push(value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Term> {
+        Ok(match self.kind {
+            Kind::List => Term::from(List::from(self.elements)),
+            Kind::Tuple => Term::from(Tuple::from(self.elements)),
+        })
+    }
+}
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
+impl ser::SerializeTupleVariant for SerializeVec {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
+
+/// Serializer of maps.
+#[doc(hidden)]
+pub struct SerializeMap {
+    serializer: Serializer,
+    entries: Vec<(Term, Term)>,
+    key: Option<Term>,
+}
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(self.serializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| {
+            Error::Custom("serialize_value called before serialize_key".to_owned())
+        })?;
+        self.entries.push((key, value.serialize(self.serializer)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Term> {
+        Ok(Term::from(Map::from(self.entries)))
+    }
+}
+
+/// Serializer of structs and struct variants.
+#[doc(hidden)]
+pub struct SerializeStruct {
+    serializer: Serializer,
+    name: &'static str,
+    variant: Option<&'static str>,
+    fields: Vec<(&'static str, Term)>,
+}
+impl SerializeStruct {
+    fn new(
+        serializer: Serializer,
+        name: &'static str,
+        variant: Option<&'static str>,
+        len: usize,
+    ) -> Self {
+        SerializeStruct {
+            serializer,
+            name,
+            variant,
+            fields: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.fields.push((key, value.serialize(self.serializer)?));
+        Ok(())
+    }
+
+    /// Skips a field, which keeps its position in a record as `undefined`.
+    fn skip(&mut self, key: &'static str) -> Result<()> {
+        if self.serializer.struct_repr == StructRepr::Record {
+            self.fields.push((key, atom("undefined")));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Term> {
+        let fields = self.fields.into_iter();
+        match self.serializer.struct_repr {
+            StructRepr::Record => Ok(tagged(self.name, fields.map(|(_, v)| v).collect())),
+            StructRepr::ElixirStruct if self.variant.is_none() => {
+                let builder = elixir::StructBuilder::new(self.name);
+                Ok(Term::from(
+                    fields.fold(builder, |b, (k, v)| b.field(k, v)).build(),
+                ))
+            }
+            StructRepr::Map | StructRepr::ElixirStruct => {
+                let map = Term::from(Map::from(
+                    fields.map(|(k, v)| (atom(k), v)).collect::<Vec<_>>(),
+                ));
+                match self.variant {
+                    Some(variant) => Ok(tagged(variant, vec![map])),
+                    None => Ok(map),
+                }
+            }
+        }
+    }
+}
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.skip(key)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
+impl ser::SerializeStructVariant for SerializeStruct {
+    type Ok = Term;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.skip(key)
+    }
+    fn end(self) -> Result<Term> {
+        self.finish()
+    }
+}
@@ -0,0 +1,208 @@
+//! Descriptions of the terms which patterns match, in the form of Erlang types.
+//!
+//! A [`Spec`] is made by [`Pattern::spec`](crate::pattern::Pattern::spec), and can be
+//! inspected as a value or displayed as an Erlang type, e.g. for documentation.
+//!
+//! # Examples
+//!
+//! ```
+//! use eetf::pattern::{any, Pattern, Uint};
+//! use eetf::Binary;
+//!
+//! let pattern = ("ok", Uint, any::<Binary>());
+//! assert_eq!("{ok, non_neg_integer(), binary()}", pattern.spec().to_string());
+//! ```
+use super::*;
+
+/// A description of a set of terms, corresponding to an Erlang type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Spec {
+    /// Any term, `term()`.
+    Any,
+
+    /// A type without parameters, such as `atom()`, `float()` or `pid()`.
+    Builtin(&'static str),
+
+    /// The atom of the name.
+    Atom(String),
+
+    /// An integer between the optional bounds (inclusive), such as `0..255`,
+    /// `non_neg_integer()` or `1` (if both bounds are the same).
+    Integer {
+        min: Option<i128>,
+        max: Option<i128>,
+    },
+
+    /// A bitstring of `size + n * unit` bits for any `n`, such as `<<_:8, _:_*8>>`.
+    Bits { size: usize, unit: usize },
+
+    /// A tuple of the elements.
+    Tuple(Vec<Spec>),
+
+    /// A proper list of elements of the type, `[T]`.
+    List(Box<Spec>),
+
+    /// A non-empty proper list of elements of the type, `nonempty_list(T)`.
+    NonEmptyList(Box<Spec>),
+
+    /// A list of the elements.
+    ///
+    /// Erlang has no type for lists of fixed lengths, so it is displayed as
+    /// `nonempty_list(T)` of the union of the elements.
+    FixedList(Vec<Spec>),
+
+    /// A map of the entries, such as `#{id := integer(), name => binary()}`.
+    Map(Vec<MapEntrySpec>),
+
+    /// Any of the types, such as `ok | {error, term()}` (`none()` if there are none).
+    Union(Vec<Spec>),
+}
+impl Spec {
+    /// Makes the union of `specs`, flattening nested unions and removing duplicates.
+    pub fn union(specs: Vec<Spec>) -> Self {
+        let mut flattened: Vec<Spec> = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let specs = match spec {
+                Spec::Union(specs) => specs,
+                spec => vec![spec],
+            };
+            for spec in specs {
+                if !flattened.contains(&spec) {
+                    flattened.push(spec);
+                }
+            }
+        }
+        if flattened.len() == 1 {
+            flattened.remove(0)
+        } else {
+            Spec::Union(flattened)
+        }
+    }
+
+    /// Makes the spec of the term.
+    ///
+    /// Atoms, integers and tuples of those are singleton types, while other terms are
+    /// described by their base types (e.g. `<<"abc">>` by `<<_:24>>` and `1.5` by `float()`).
+    pub fn literal(term: Term) -> Self {
+        match term {
+            Term::Atom(x) => Spec::Atom(x.name),
+            Term::FixInteger(x) => Spec::Integer {
+                min: Some(x.value.into()),
+                max: Some(x.value.into()),
+            },
+            Term::BigInteger(x) => match x.value.to_i128() {
+                Some(n) => Spec::Integer {
+                    min: Some(n),
+                    max: Some(n),
+                },
+                None => Spec::Builtin("integer()"),
+            },
+            Term::Float(_) => Spec::Builtin("float()"),
+            Term::Binary(x) => Spec::Bits {
+                size: x.bytes.len() * 8,
+                unit: 0,
+            },
+            Term::BitBinary(x) => Spec::Bits {
+                size: match x.bytes.len() {
+                    0 => 0,
+                    n => (n - 1) * 8 + x.tail_bits_size as usize,
+                },
+                unit: 0,
+            },
+            Term::List(x) => Spec::FixedList(x.elements.into_iter().map(Spec::literal).collect()),
+            Term::ImproperList(_) => Spec::Builtin("nonempty_improper_list(term(), term())"),
+            Term::Tuple(x) => Spec::Tuple(x.elements.into_iter().map(Spec::literal).collect()),
+            Term::Map(x) => Spec::Map(
+                x.entries
+                    .into_iter()
+                    .map(|(k, v)| MapEntrySpec {
+                        key: Spec::literal(k),
+                        value: Spec::literal(v),
+                        required: true,
+                    })
+                    .collect(),
+            ),
+            Term::Pid(_) => Spec::Builtin("pid()"),
+            Term::Port(_) => Spec::Builtin("port()"),
+            Term::Reference(_) => Spec::Builtin("reference()"),
+            Term::ExternalFun(_) | Term::InternalFun(_) => Spec::Builtin("fun()"),
+        }
+    }
+}
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Spec::Any => write!(f, "term()"),
+            Spec::Builtin(name) => write!(f, "{}", name),
+            Spec::Atom(ref name) => write!(f, "{}", Atom::from(name.as_str())),
+            Spec::Integer { min, max } => match (min, max) {
+                (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+                (Some(min), Some(max)) => write!(f, "{}..{}", min, max),
+                (Some(0), None) => write!(f, "non_neg_integer()"),
+                (Some(1), None) => write!(f, "pos_integer()"),
+                (None, Some(-1)) => write!(f, "neg_integer()"),
+                _ => write!(f, "integer()"),
+            },
+            Spec::Bits { size, unit } => match (size, unit) {
+                (0, 0) => write!(f, "<<>>"),
+                (0, 1) => write!(f, "bitstring()"),
+                (0, 8) => write!(f, "binary()"),
+                (size, 0) => write!(f, "<<_:{}>>", size),
+                (0, unit) => write!(f, "<<_:_*{}>>", unit),
+                (size, unit) => write!(f, "<<_:{}, _:_*{}>>", size, unit),
+            },
+            Spec::Tuple(ref elements) => {
+                write!(f, "{{")?;
+                write_separated(f, elements, ", ")?;
+                write!(f, "}}")
+            }
+            Spec::List(ref element) => write!(f, "[{}]", element),
+            Spec::NonEmptyList(ref element) => write!(f, "nonempty_list({})", element),
+            Spec::FixedList(ref elements) => {
+                if elements.is_empty() {
+                    write!(f, "[]")
+                } else {
+                    let element = Spec::union(elements.clone());
+                    write!(f, "nonempty_list({})", element)
+                }
+            }
+            Spec::Map(ref entries) => {
+                write!(f, "#{{")?;
+                write_separated(f, entries, ", ")?;
+                write!(f, "}}")
+            }
+            Spec::Union(ref specs) if specs.is_empty() => write!(f, "none()"),
+            Spec::Union(ref specs) => write_separated(f, specs, " | "),
+        }
+    }
+}
+
+/// An entry of a [`Spec::Map`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapEntrySpec {
+    pub key: Spec,
+    pub value: Spec,
+
+    /// Whether the key must be present (`:=`) or not (`=>`).
+    pub required: bool,
+}
+impl fmt::Display for MapEntrySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = if self.required { ":=" } else { "=>" };
+        write!(f, "{} {} {}", self.key, op, self.value)
+    }
+}
+
+fn write_separated<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    items: &[T],
+    separator: &str,
+) -> fmt::Result {
+    for (i, x) in items.iter().enumerate() {
+        if i != 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", x)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Lexical rules of the Erlang term syntax shared by the parser and the formatters.
+use std::fmt;
+
+const RESERVED_WORDS: &[&str] = &[
+    "after", "and", "andalso", "band", "begin", "bnot", "bor", "bsl", "bsr", "bxor", "case",
+    "catch", "cond", "div", "else", "end", "fun", "if", "let", "maybe", "not", "of", "or",
+    "orelse", "receive", "rem", "try", "when", "xor",
+];
+
+/// Returns `true` if `c` can start an unquoted atom.
+pub fn is_atom_start(c: char) -> bool {
+    c.is_ascii_lowercase() || (('ß'..='ÿ').contains(&c) && c != '÷')
+}
+
+/// Returns `true` if `c` can appear in an unquoted atom after the first character.
+pub fn is_atom_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || c == '_'
+        || c == '@'
+        || (('À'..='ÿ').contains(&c) && c != '×' && c != '÷')
+}
+
+/// Returns `true` if the atom `name` has to be surrounded by single quotes.
+pub fn atom_needs_quotes(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if is_atom_start(c) => !chars.all(is_atom_char) || RESERVED_WORDS.contains(&name),
+        _ => true,
+    }
+}
+
+/// Returns `true` if `c` is printable in the sense of `io_lib:printable_list/1`
+/// (with the default `latin1` range).
+pub fn is_printable(c: char) -> bool {
+    matches!(
+        c,
+        ' '..='~' | '\u{a0}'..='\u{ff}' | '\n' | '\r' | '\t' | '\u{b}' | '\u{8}' | '\u{c}' | '\u{1b}'
+    )
+}
+
+/// Returns `true` if the list of integers `codes` would be written as a string.
+pub fn is_printable_codes<I>(codes: I) -> bool
+where
+    I: IntoIterator<Item = Option<u32>>,
+{
+    let mut empty = true;
+    for code in codes {
+        empty = false;
+        match code.and_then(std::char::from_u32) {
+            Some(c) if is_printable(c) => {}
+            _ => return false,
+        }
+    }
+    !empty
+}
+
+/// Writes `name` as an atom, quoting it only if necessary.
+pub fn write_atom<W: fmt::Write>(w: &mut W, name: &str) -> fmt::Result {
+    if atom_needs_quotes(name) {
+        write_quoted(w, name.chars(), '\'')
+    } else {
+        w.write_str(name)
+    }
+}
+
+/// Writes `chars` surrounded by `quote`, escaping characters the Erlang scanner
+/// would not read back verbatim.
+pub fn write_quoted<W, I>(w: &mut W, chars: I, quote: char) -> fmt::Result
+where
+    W: fmt::Write,
+    I: IntoIterator<Item = char>,
+{
+    w.write_char(quote)?;
+    for c in chars {
+        match c {
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{b}' => w.write_str("\\v")?,
+            '\u{8}' => w.write_str("\\b")?,
+            '\u{c}' => w.write_str("\\f")?,
+            '\u{1b}' => w.write_str("\\e")?,
+            '\u{7f}' => w.write_str("\\d")?,
+            c if c == quote => {
+                w.write_char('\\')?;
+                w.write_char(c)?;
+            }
+            c if c.is_control() => write!(w, "\\x{{{:X}}}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char(quote)
+}
+
+/// Writes `value` so that it is read back as a float (`1.0` rather than `1`).
+pub fn write_float<W: fmt::Write>(w: &mut W, value: f64) -> fmt::Result {
+    let s = format!("{:?}", value);
+    match s.find('e') {
+        Some(i) if !s[..i].contains('.') => write!(w, "{}.0{}", &s[..i], &s[i..]),
+        _ => w.write_str(&s),
+    }
+}
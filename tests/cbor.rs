@@ -0,0 +1,148 @@
+#![cfg(feature = "cbor")]
+extern crate ciborium;
+extern crate eetf;
+
+use ciborium::value::Value;
+use eetf::cbor;
+use eetf::*;
+
+fn term(s: &str) -> Term {
+    s.parse().unwrap()
+}
+
+fn round_trip(t: Term) -> Term {
+    cbor::from_slice(&cbor::to_vec(&t).unwrap()).unwrap()
+}
+
+fn bignum(tag: u64, bytes: &[u8]) -> Value {
+    Value::Tag(tag, Box::new(Value::Bytes(bytes.to_vec())))
+}
+
+#[test]
+fn to_value_test() {
+    assert_eq!(
+        Value::Array(vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::from(1),
+            Value::from(10_000_000_000i64),
+            Value::Float(2.5),
+            Value::from("abc"),
+            Value::Bytes(vec![255]),
+        ]),
+        cbor::to_value(&term("[null,true,1,10000000000,2.5,<<\"abc\">>,<<255>>]")).unwrap()
+    );
+    assert_eq!(
+        Value::Tag(cbor::ATOM_TAG, Box::new(Value::from("foo"))),
+        cbor::to_value(&term("foo")).unwrap()
+    );
+}
+
+#[test]
+fn from_value_test() {
+    assert_eq!(
+        term("[null,false,-1,18446744073709551615,<<\"a\">>,<<1,2>>]"),
+        cbor::from_value(&Value::Array(vec![
+            Value::Null,
+            Value::Bool(false),
+            Value::from(-1),
+            Value::from(u64::MAX),
+            Value::from("a"),
+            Value::Bytes(vec![1, 2]),
+        ]))
+        .unwrap()
+    );
+    assert!(cbor::from_slice(&[0xff]).is_err());
+}
+
+#[test]
+fn float_test() {
+    assert_eq!(term("1.5"), cbor::from_value(&Value::Float(1.5)).unwrap());
+    for x in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert!(cbor::from_value(&Value::Float(*x)).is_err());
+    }
+    // half-precision float: NaN, Infinity
+    assert!(cbor::from_slice(&[0xf9, 0x7e, 0x00]).is_err());
+    assert!(cbor::from_slice(&[0xf9, 0x7c, 0x00]).is_err());
+}
+
+#[test]
+fn integer_boundary_test() {
+    // CBOR integers cover -2^64 to 2^64-1
+    for s in &["18446744073709551615", "-18446744073709551616"] {
+        let value = cbor::to_value(&term(s)).unwrap();
+        assert!(value.is_integer(), "{}", s);
+        assert_eq!(term(s), cbor::from_value(&value).unwrap(), "{}", s);
+    }
+
+    // Beyond them are bignums (tags 2 and 3), whose bytes are `n` and `-1 - n`
+    let mut i128_edge = vec![0x7f];
+    i128_edge.extend_from_slice(&[0xff; 15]);
+    for (s, value) in &[
+        (
+            "18446744073709551616",
+            bignum(2, &[1, 0, 0, 0, 0, 0, 0, 0, 0]),
+        ),
+        (
+            "-18446744073709551617",
+            bignum(3, &[1, 0, 0, 0, 0, 0, 0, 0, 0]),
+        ),
+        (
+            "170141183460469231731687303715884105727",
+            bignum(2, &i128_edge),
+        ),
+        (
+            "-170141183460469231731687303715884105728",
+            bignum(3, &i128_edge),
+        ),
+        (
+            "340282366920938463463374607431768211456",
+            bignum(2, &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+        ),
+    ] {
+        assert_eq!(*value, cbor::to_value(&term(s)).unwrap(), "{}", s);
+        assert_eq!(term(s), cbor::from_value(value).unwrap(), "{}", s);
+        assert_eq!(term(s), round_trip(term(s)), "{}", s);
+    }
+
+    // Non-canonical bignums are accepted
+    assert_eq!(term("1"), cbor::from_value(&bignum(2, &[0, 1])).unwrap());
+    assert_eq!(term("-1"), cbor::from_value(&bignum(3, &[])).unwrap());
+    assert!(cbor::from_value(&Value::Tag(2, Box::new(Value::from(1)))).is_err());
+}
+
+#[test]
+fn tag_test() {
+    for s in &[
+        "'hello world'",
+        "{ok,{1,2},{}}",
+        "[1,2|3]",
+        "<<1,2:3>>",
+        "<nonode@nohost.1.2>",
+        "#Ref<nonode@nohost.1.2.3>",
+        "#Port<nonode@nohost.3>",
+        "fun lists:map/2",
+    ] {
+        assert_eq!(term(s), round_trip(term(s)), "{}", s);
+    }
+
+    // Tags of other applications, such as the standard date/time tags
+    for tag in &[0, 1, 32, cbor::TERM_TAG + 1] {
+        let value = Value::Tag(*tag, Box::new(Value::from("x")));
+        assert!(cbor::from_value(&value).is_err(), "{}", tag);
+    }
+    // Unexpected contents of the tags used here
+    for (tag, content) in &[
+        (cbor::ATOM_TAG, Value::Bytes(b"foo".to_vec())),
+        (cbor::TUPLE_TAG, Value::Null),
+        (cbor::IMPROPER_LIST_TAG, Value::Array(vec![Value::from(1)])),
+        (
+            cbor::BITSTRING_TAG,
+            Value::Array(vec![Value::from(9), Value::Bytes(vec![1])]),
+        ),
+        (cbor::TERM_TAG, Value::Bytes(vec![131, 255])),
+    ] {
+        let value = Value::Tag(*tag, Box::new(content.clone()));
+        assert!(cbor::from_value(&value).is_err(), "{:?}", value);
+    }
+}
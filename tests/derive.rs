@@ -0,0 +1,230 @@
+#![cfg(feature = "derive")]
+extern crate eetf;
+
+use eetf::*;
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+#[eetf(record = "user")]
+struct User {
+    id: u32,
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+struct Config {
+    #[eetf(rename = "max_conns")]
+    max_connections: u16,
+    verbose: bool,
+    tags: Vec<Atom>,
+    timeout: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+#[eetf(record)]
+struct HttpRequest(String, i64);
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+struct UserId(u32);
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+#[eetf(tuple)]
+struct Point {
+    x: Float,
+    y: Float,
+}
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+struct Ping;
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+#[eetf(atom)]
+enum Level {
+    Debug,
+    #[eetf(rename = "warning")]
+    Warn,
+    InfoMessages,
+}
+
+#[derive(Debug, PartialEq, ToTerm, FromTerm)]
+enum Reply<T> {
+    Ok(T),
+    Error(Atom),
+    Moved {
+        node: Atom,
+        id: u32,
+    },
+    #[eetf(map)]
+    Redirect {
+        location: String,
+    },
+    Timeout,
+}
+
+fn term(s: &str) -> Term {
+    s.parse().unwrap()
+}
+
+fn round_trip<T: ToTerm + FromTerm + std::fmt::Debug + PartialEq>(value: T, s: &str) {
+    assert_eq!(term(s), value.to_term());
+    assert_eq!(value, T::from_term(term(s)).unwrap());
+}
+
+#[test]
+fn derive_struct_test() {
+    round_trip(
+        User {
+            id: 1,
+            name: "alice".to_owned(),
+            email: None,
+        },
+        "{user,1,<<\"alice\">>,undefined}",
+    );
+    round_trip(
+        Config {
+            max_connections: 10,
+            verbose: true,
+            tags: vec![Atom::from("a")],
+            timeout: Some(5000),
+        },
+        "#{max_conns => 10,verbose => true,tags => [a],timeout => 5000}",
+    );
+    round_trip(
+        HttpRequest("/".to_owned(), 10_000_000_000),
+        "{http_request,<<\"/\">>,10000000000}",
+    );
+    round_trip(UserId(7), "7");
+    round_trip(
+        Point {
+            x: Float::try_from(1.0).unwrap(),
+            y: Float::try_from(2.5).unwrap(),
+        },
+        "{1.0,2.5}",
+    );
+    round_trip(Ping, "ping");
+
+    // Missing optional keys and extra keys
+    assert_eq!(
+        Config {
+            max_connections: 1,
+            verbose: false,
+            tags: vec![],
+            timeout: None,
+        },
+        Config::from_term(term(
+            "#{max_conns => 1,verbose => false,tags => [],extra => 1}"
+        ))
+        .unwrap()
+    );
+    // Charlists are accepted for strings
+    assert_eq!(
+        "alice",
+        User::from_term(term("{user,1,\"alice\",undefined}"))
+            .unwrap()
+            .name
+    );
+
+    // Errors
+    assert!(Config::from_term(term("#{verbose => true,tags => []}")).is_err());
+    assert!(User::from_term(term("{person,1,<<\"alice\">>,undefined}")).is_err());
+    assert!(User::from_term(term("{user,1,<<\"alice\">>}")).is_err());
+    assert!(UserId::from_term(term("-7")).is_err());
+}
+
+#[test]
+fn derive_enum_test() {
+    round_trip(Level::Debug, "debug");
+    round_trip(Level::Warn, "warning");
+    round_trip(Level::InfoMessages, "info_messages");
+
+    round_trip(Reply::Ok(1u8), "{ok,1}");
+    round_trip(Reply::<u8>::Error(Atom::from("enoent")), "{error,enoent}");
+    round_trip(
+        Reply::<u8>::Moved {
+            node: Atom::from("a@b"),
+            id: 2,
+        },
+        "{moved,'a@b',2}",
+    );
+    round_trip(
+        Reply::<u8>::Redirect {
+            location: "/x".to_owned(),
+        },
+        "{redirect,#{location => <<\"/x\">>}}",
+    );
+    round_trip(Reply::<u8>::Timeout, "timeout");
+
+    assert!(Level::from_term(term("info")).is_err());
+    assert!(Reply::<u8>::from_term(term("{ok,1,2}")).is_err());
+    assert!(Reply::<u8>::from_term(term("{ok,256}")).is_err());
+}
+
+#[test]
+fn term_match_test() {
+    use eetf::pattern::{Range, U8};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let describe = |term: &Term| {
+        term_match!(term,
+            {ok, #{name := Name, age := Age @ U8}} => format!("{} is {}", Name, Age),
+            {ok, #{name := _}} => "anonymous age".to_owned(),
+            {error, {badarg, <<"x">>}} => "badarg".to_owned(),
+            {error, Reason} => format!("error: {}", Reason),
+            [] => "empty".to_owned(),
+            [a | [B, c]] => format!("a, {}, c", B),
+            [H | T] => format!("{} then {}", H, T),
+            {point, X @ (Range(0..10)), -1, _} => format!("x={}", X),
+        )
+        .map_err(|e| e.depth())
+    };
+
+    assert_eq!(
+        Ok("<<97>> is 3".to_owned()),
+        describe(&parse("{ok,#{name => <<\"a\">>,age => 3,extra => 1}}"))
+    );
+    assert_eq!(
+        Ok("anonymous age".to_owned()),
+        describe(&parse("{ok,#{name => a,age => 300}}"))
+    );
+    assert_eq!(
+        Ok("badarg".to_owned()),
+        describe(&parse("{error,{badarg,<<\"x\">>}}"))
+    );
+    assert_eq!(
+        Ok("error: timeout".to_owned()),
+        describe(&parse("{error,timeout}"))
+    );
+    assert_eq!(Ok("empty".to_owned()), describe(&parse("[]")));
+    assert_eq!(Ok("1 then [2,3]".to_owned()), describe(&parse("[1,2,3]")));
+    assert_eq!(Ok("1 then []".to_owned()), describe(&parse("[1]")));
+    assert_eq!(Ok("1 then x".to_owned()), describe(&parse("[1|x]")));
+    assert_eq!(Ok("1 then [2|x]".to_owned()), describe(&parse("[1,2|x]")));
+    assert_eq!(Ok("a, b, c".to_owned()), describe(&parse("[a,b,c]")));
+    assert_eq!(
+        Ok("a then [b,c,d]".to_owned()),
+        describe(&parse("[a,b,c,d]"))
+    );
+    assert_eq!(Ok("x=5".to_owned()), describe(&parse("{point,5,-1,z}")));
+
+    // The deepest failure is reported
+    assert_eq!(Err(1), describe(&parse("{ok,#{age => 1}}")));
+    assert_eq!(Err(0), describe(&parse("foo")));
+
+    // Tails are matched as lists, not element-wise
+    let term = parse("[1,[]]");
+    assert!(term_match!(term, [_ | []] => ()).is_err());
+    let term = parse("[1,2,3|x]");
+    let rest = term_match!(term, [_, _ | Rest] => Rest.to_term());
+    assert_eq!(parse("[3|x]"), rest.unwrap());
+    assert!(term_match!(term, [_, _, _, _ | _] => ()).is_err());
+
+    // Bindings of large tuples
+    let term = parse("{r,1,2,3,4,5,6,7,8,9,10,11,12,13}");
+    let sum = term_match!(term,
+        {r, A, B, C, D, E, F, G, H, I, J, K, L, M @ U8} => {
+            [A, B, C, D, E, F, G, H, I, J, K, L].len() + M as usize
+        }
+    );
+    assert_eq!(25, sum.unwrap());
+}
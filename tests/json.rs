@@ -0,0 +1,136 @@
+#![cfg(feature = "json")]
+extern crate eetf;
+extern crate serde_json;
+
+use eetf::json::{self, Keys, Mode, Options};
+use eetf::*;
+use serde_json::json;
+
+fn term(s: &str) -> Term {
+    s.parse().unwrap()
+}
+
+#[test]
+fn from_value_test() {
+    assert_eq!(
+        term("[null,true,false,1,-2,2.5,<<\"héllo\"/utf8>>]"),
+        json::from_value(&json!([null, true, false, 1, -2, 2.5, "héllo"]))
+    );
+    assert_eq!(
+        term("[10000000000,18446744073709551615]"),
+        json::from_value(&json!([10_000_000_000i64, u64::MAX]))
+    );
+    assert_eq!(
+        term("#{<<\"a\">> => #{<<\"b\">> => []}}"),
+        json::from_value(&json!({"a": {"b": []}}))
+    );
+
+    let options = Options::new().keys(Keys::Atom).null("nil");
+    assert_eq!(
+        term("#{a => nil,b => [#{c => 1}]}"),
+        options.to_term(&json!({"a": null, "b": [{"c": 1}]}))
+    );
+    // Tags are only interpreted in the tagged mode
+    assert_eq!(
+        term("#{<<\"$atom\">> => <<\"a\">>}"),
+        json::from_value(&json!({"$atom": "a"}))
+    );
+}
+
+#[test]
+fn to_value_test() {
+    assert_eq!(
+        json!([null, true, "ok", 1, 2.5, "abc", [1, 2]]),
+        json::to_value(&term("[null,true,ok,1,2.5,<<\"abc\">>,\"\\x{1}\\x{2}\"]")).unwrap()
+    );
+    assert_eq!(
+        json!({"a": 1, "b": 2}),
+        json::to_value(&term("#{a => 1,<<\"b\">> => 2}")).unwrap()
+    );
+    assert_eq!(
+        json!(null),
+        Options::new()
+            .null("undefined")
+            .to_value(&term("undefined"))
+            .unwrap()
+    );
+
+    // Strict mode
+    for s in &[
+        "{a,b}",
+        "<nonode@nohost.1.2>",
+        "#Ref<nonode@nohost.1.2.3>",
+        "fun lists:map/2",
+        "<<255>>",
+        "<<1:3>>",
+        "[1|2]",
+        "#{1 => 2}",
+        "100000000000000000000000",
+    ] {
+        assert!(json::to_value(&term(s)).is_err(), "{}", s);
+    }
+
+    // Lossy mode
+    let lossy = Options::new().mode(Mode::Lossy);
+    assert_eq!(
+        json!([["a", "b"], "<nonode@nohost.1.2>", "\u{fffd}", [1, 2], {"1": 2}, "100000000000000000000000"]),
+        lossy
+            .to_value(&term(
+                "[{a,b},<nonode@nohost.1.2>,<<255>>,[1|2],#{1 => 2},100000000000000000000000]"
+            ))
+            .unwrap()
+    );
+}
+
+#[test]
+fn tagged_round_trip_test() {
+    let tagged = Options::new().mode(Mode::Tagged);
+    assert_eq!(
+        json!({"$tuple": [{"$atom": "ok"}, true, null]}),
+        tagged.to_value(&term("{ok,true,null}")).unwrap()
+    );
+    assert_eq!(
+        json!({"$map": [[{"$atom": "a"}, 1]]}),
+        tagged.to_value(&term("#{a => 1}")).unwrap()
+    );
+    assert_eq!(
+        json!({"a": 1, "b": 2}),
+        tagged
+            .to_value(&term("#{<<\"a\">> => 1,<<\"b\">> => 2}"))
+            .unwrap()
+    );
+    assert_eq!(
+        json!({"$map": [["b", 1], ["a", 2]]}),
+        tagged
+            .to_value(&term("#{<<\"b\">> => 1,<<\"a\">> => 2}"))
+            .unwrap()
+    );
+
+    let internal_fun = Term::from(InternalFun::New {
+        module: Atom::from("m"),
+        arity: 1,
+        pid: Pid::from(("nonode@nohost", 1, 2)),
+        free_vars: vec![term("{x}")],
+        index: 3,
+        uniq: [7; 16],
+        old_index: 4,
+        old_uniq: 5,
+    });
+    let terms = vec![
+        term("[ok,{},{a,[1|2]},<<255,0>>,<<1:3>>,<<\"text\">>,100000000000000000000000]"),
+        term("#{<<\"k\">> => #{a => 1,1 => [x]},<<\"l\">> => -100000000000000000000000}"),
+        term("#{<<\"b\">> => 1,<<\"a\">> => 2,<<\"c\">> => #{<<\"z\">> => 3,<<\"y\">> => 4}}"),
+        term("{<nonode@nohost.1.2>,#Port<n@h.3>,#Ref<n@h.1.2.3>,fun lists:map/2,1.5}"),
+        internal_fun,
+    ];
+    for t in &terms {
+        let value = tagged.to_value(t).unwrap();
+        let text = serde_json::to_string(&value).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(*t, tagged.to_term(&parsed), "{}", text);
+    }
+
+    let atom_keys = Options::new().mode(Mode::Tagged).keys(Keys::Atom);
+    let t = term("#{b => 1,a => #{<<\"c\">> => 2}}");
+    assert_eq!(t, atom_keys.to_term(&atom_keys.to_value(&t).unwrap()));
+}
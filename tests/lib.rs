@@ -1,18 +1,30 @@
 extern crate eetf;
 extern crate num;
 
-use eetf::convert::TryInto;
+use eetf::convert::TryAsRef;
 use eetf::*;
-use std::convert::TryFrom;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
 use std::io::Cursor;
 
 #[test]
 fn atom_test() {
     // Display
-    assert_eq!("'foo'", Atom::from("foo").to_string());
+    assert_eq!("foo", Atom::from("foo").to_string());
+    assert_eq!("foo@bar_1", Atom::from("foo@bar_1").to_string());
+    assert_eq!("'Hello world'", Atom::from("Hello world").to_string());
+    assert_eq!("'foo-bar'", Atom::from("foo-bar").to_string());
+    assert_eq!("'receive'", Atom::from("receive").to_string());
+    assert_eq!("''", Atom::from("").to_string());
+    assert_eq!(r#"'a\nb'"#, Atom::from("a\nb").to_string());
     assert_eq!(r#"'fo\'o'"#, Atom::from(r#"fo'o"#).to_string());
     assert_eq!(r#"'fo\\o'"#, Atom::from(r#"fo\o"#).to_string());
 
+    // Parse
+    assert_eq!(Ok(Term::from(Atom::from("foo"))), "foo".parse());
+    assert_eq!(Ok(Term::from(Atom::from("fo'o"))), r#"'fo\'o'"#.parse());
+    assert_eq!(Ok(Term::from(Atom::from("a b\n"))), r#"'a\sb\n'"#.parse());
+
     // Decode
     assert_eq!(
         Ok(Atom::from("foo")),
@@ -46,6 +58,17 @@ fn integer_test() {
     assert_eq!("-123", FixInteger::from(-123).to_string());
     assert_eq!("-123", BigInteger::from(-123).to_string());
 
+    // Parse
+    assert_eq!(Ok(Term::from(FixInteger::from(123))), "123".parse());
+    assert_eq!(Ok(Term::from(FixInteger::from(-123))), "-123".parse());
+    assert_eq!(Ok(Term::from(FixInteger::from(255))), "16#FF".parse());
+    assert_eq!(Ok(Term::from(FixInteger::from(97))), "$a".parse());
+    assert_eq!(Ok(Term::from(FixInteger::from(10))), "$\\n".parse());
+    assert_eq!(
+        Ok(Term::from(BigInteger::from(10000000000u64))),
+        "10_000_000_000".parse()
+    );
+
     // Decode
     assert_eq!(Ok(FixInteger::from(10)), decode(&[131, 97, 10]).try_into()); // SMALL_INTEGER_EXT
     assert_eq!(
@@ -100,10 +123,21 @@ fn integer_test() {
 #[test]
 fn float_test() {
     // Display
-    assert_eq!("123", Float::try_from(123.0).unwrap().to_string());
+    assert_eq!("123.0", Float::try_from(123.0).unwrap().to_string());
+    assert_eq!("1.0e20", Float::try_from(1e20).unwrap().to_string());
     assert_eq!("123.4", Float::try_from(123.4).unwrap().to_string());
     assert_eq!("-123.4", Float::try_from(-123.4).unwrap().to_string());
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Float::try_from(123.4).unwrap())),
+        "123.4".parse()
+    );
+    assert_eq!(
+        Ok(Term::from(Float::try_from(-1.5e-3).unwrap())),
+        "-1.5e-3".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(Float::try_from("1.23".parse::<f32>().unwrap() as f64).unwrap()),
@@ -149,10 +183,16 @@ fn float_test() {
 fn pid_test() {
     // Display
     assert_eq!(
-        r#"<'nonode@nohost'.1.2>"#,
+        r#"<nonode@nohost.1.2>"#,
         Pid::from(("nonode@nohost", 1, 2)).to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Pid::from(("nonode@nohost", 1, 2)))),
+        "<'nonode@nohost'.1.2>".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(Pid::from(("nonode@nohost", 49, 0))),
@@ -177,10 +217,16 @@ fn pid_test() {
 fn port_test() {
     // Display
     assert_eq!(
-        r#"#Port<'nonode@nohost'.1>"#,
+        r#"#Port<nonode@nohost.1>"#,
         Port::from(("nonode@nohost", 1)).to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Port::from(("nonode@nohost", 1)))),
+        "#Port<'nonode@nohost'.1>".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(Port::from(("nonode@nohost", 366))),
@@ -205,10 +251,19 @@ fn port_test() {
 fn reference_test() {
     // Display
     assert_eq!(
-        r#"#Ref<'nonode@nohost'.1>"#,
+        r#"#Ref<nonode@nohost.1>"#,
         Reference::from(("nonode@nohost", 1)).to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Reference::from((
+            "nonode@nohost",
+            vec![1, 2, 3]
+        )))),
+        "#Ref<'nonode@nohost'.1.2.3>".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(Reference::from(("nonode@nohost", vec![138016, 262145, 0]))),
@@ -235,10 +290,16 @@ fn reference_test() {
 fn external_fun_test() {
     // Display
     assert_eq!(
-        r#"fun 'foo':'bar'/3"#,
+        r#"fun foo:bar/3"#,
         ExternalFun::from(("foo", "bar", 3)).to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(ExternalFun::from(("foo", "bar", 3)))),
+        "fun 'foo':'bar'/3".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(ExternalFun::from(("foo", "bar", 3))),
@@ -284,6 +345,20 @@ fn binary_test() {
     // Display
     assert_eq!("<<1,2,3>>", Binary::from(vec![1, 2, 3]).to_string());
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Binary::from(vec![1, 2, 3]))),
+        "<<1,2,3>>".parse()
+    );
+    assert_eq!(
+        Ok(Term::from(Binary::from(vec![97, 98, 0, 1]))),
+        r#"<<"ab", 1:16>>"#.parse()
+    );
+    assert_eq!(
+        Ok(Term::from(Binary::from(vec![1, 0, 195, 169]))),
+        r#"<<1:16/little, "é"/utf8>>"#.parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(Binary::from(vec![1, 2, 3])),
@@ -307,6 +382,22 @@ fn bit_binary_test() {
         BitBinary::from((vec![1, 2, 3], 5)).to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(BitBinary::from((vec![1, 2, 3], 5)))),
+        "<<1,2,3:5>>".parse()
+    );
+    let parse = |s: &str| s.parse::<Term>();
+    assert_eq!(parse("<<1,0:4>>"), parse("<<1:12/little>>"));
+    assert_eq!(parse("<<16#23,1:4>>"), parse("<<16#123:12/little>>"));
+    assert_eq!(parse("<<16#12,3:4>>"), parse("<<16#123:12>>"));
+    assert_eq!(parse("<<1:3>>"), parse("<<<<1:3,2:5>>:3/bits>>"));
+    assert_eq!(parse("<<1,2>>"), parse("<<<<1,2,3>>:2/binary>>"));
+    assert_eq!(parse("<<\"a\">>"), parse("<<\"ab\":8/bitstring>>"));
+    assert!(parse("<<\"日\"/binary>>").is_err());
+    assert!(parse("<<1:4000000000>>").is_err());
+    assert!(parse("<<<<1>>:4000000000/binary>>").is_err());
+
     // Decode
     assert_eq!(
         Ok(BitBinary::from((vec![1, 2, 3], 5))),
@@ -324,7 +415,7 @@ fn bit_binary_test() {
 fn list_test() {
     // Display
     assert_eq!(
-        "['a',1]",
+        "[a,1]",
         List::from(vec![
             Term::from(Atom::from("a")),
             Term::from(FixInteger::from(1))
@@ -332,6 +423,33 @@ fn list_test() {
         .to_string()
     );
     assert_eq!("[]", List::nil().to_string());
+    assert_eq!(
+        r#""a\"b\n""#,
+        List::from(
+            "a\"b\n"
+                .chars()
+                .map(|c| Term::from(FixInteger::from(c as i32)))
+                .collect::<Vec<_>>()
+        )
+        .to_string()
+    );
+
+    // Parse
+    assert_eq!(Ok(Term::from(List::nil())), "[]".parse());
+    assert_eq!(
+        Ok(Term::from(List::from(vec![
+            Term::from(Atom::from("a")),
+            Term::from(FixInteger::from(1))
+        ]))),
+        "['a', 1]".parse()
+    );
+    assert_eq!(
+        Ok(Term::from(List::from(vec![
+            Term::from(FixInteger::from(104)),
+            Term::from(FixInteger::from(105))
+        ]))),
+        r#""hi""#.parse()
+    );
 
     // Decode
     assert_eq!(Ok(List::nil()), decode(&[131, 106]).try_into()); // NIL_EXT
@@ -366,7 +484,7 @@ fn list_test() {
 fn improper_list_test() {
     // Display
     assert_eq!(
-        "[0,'a'|1]",
+        "[0,a|1]",
         ImproperList::from((
             vec![Term::from(FixInteger::from(0)), Term::from(Atom::from("a"))],
             Term::from(FixInteger::from(1))
@@ -374,6 +492,22 @@ fn improper_list_test() {
         .to_string()
     );
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(ImproperList::from((
+            vec![Term::from(FixInteger::from(0)), Term::from(Atom::from("a"))],
+            Term::from(FixInteger::from(1))
+        )))),
+        "[0,'a'|1]".parse()
+    );
+    assert_eq!(
+        Ok(Term::from(List::from(vec![
+            Term::from(FixInteger::from(0)),
+            Term::from(FixInteger::from(1))
+        ]))),
+        "[0|[1]]".parse()
+    );
+
     // Decode
     assert_eq!(
         Ok(ImproperList::from((
@@ -397,7 +531,7 @@ fn improper_list_test() {
 fn tuple_test() {
     // Display
     assert_eq!(
-        "{'a',1}",
+        "{a,1}",
         Tuple::from(vec![
             Term::from(Atom::from("a")),
             Term::from(FixInteger::from(1))
@@ -406,6 +540,16 @@ fn tuple_test() {
     );
     assert_eq!("{}", Tuple::from(vec![]).to_string());
 
+    // Parse
+    assert_eq!(
+        Ok(Term::from(Tuple::from(vec![
+            Term::from(Atom::from("a")),
+            Term::from(FixInteger::from(1))
+        ]))),
+        "{a, 1}".parse()
+    );
+    assert_eq!(Ok(Term::from(Tuple::from(vec![]))), "{}".parse());
+
     // Decode
     assert_eq!(
         Ok(Tuple::from(vec![
@@ -436,10 +580,13 @@ fn map_test() {
     ]);
 
     // Display
-    assert_eq!("#{1=>2,'a'=>'b'}", map.to_string());
+    assert_eq!("#{1=>2,a=>b}", map.to_string());
 
     assert_eq!("#{}", Map::from(vec![]).to_string());
 
+    // Parse
+    assert_eq!(Ok(Term::from(map.clone())), "#{1 => 2, a => b}".parse());
+
     // Decode
     assert_eq!(
         Ok(map.clone()),
@@ -488,6 +635,1014 @@ fn compressed_term_test() {
     );
 }
 
+#[test]
+fn parse_test() {
+    let term: Term = "{ok, [1, <<\"a\">>, #{k => v}]}.".parse().unwrap();
+    assert_eq!(
+        Term::from(Tuple::from(vec![
+            Term::from(Atom::from("ok")),
+            Term::from(List::from(vec![
+                Term::from(FixInteger::from(1)),
+                Term::from(Binary::from(vec![97])),
+                Term::from(Map::from(vec![(
+                    Term::from(Atom::from("k")),
+                    Term::from(Atom::from("v"))
+                )]))
+            ]))
+        ])),
+        term
+    );
+
+    // Round trip
+    for s in &[
+        "{a,1,[2,3|4],#{5=><<6,7:3>>}}",
+        "[<nonode@nohost.1.2>,#Port<nonode@nohost.3>,#Ref<nonode@nohost.4.5>]",
+        "fun lists:map/2",
+        "{'Hello world','foo-bar','receive',\"a\\\"b\\n\",[-1,2.5,1.0e-7]}",
+    ] {
+        let term: Term = s.parse().unwrap();
+        assert_eq!(*s, term.to_string());
+    }
+
+    // Errors
+    assert_eq!(Err(ParseError::UnexpectedEof), "{a,".parse::<Term>());
+    assert_eq!(
+        Err(ParseError::UnexpectedChar {
+            position: 3,
+            found: 'b'
+        }),
+        "{a b}".parse::<Term>()
+    );
+    assert!("Foo".parse::<Term>().is_err());
+    assert!("#Fun<'a'.0.1>".parse::<Term>().is_err());
+
+    // Nesting is bounded
+    let nested = |open: &str, close: &str, n: usize| open.repeat(n) + &close.repeat(n);
+    assert!(nested("[", "]", 128).parse::<Term>().is_ok());
+    assert_eq!(
+        Err(ParseError::TooDeep { position: 128 }),
+        nested("{", "}", 129).parse::<Term>()
+    );
+    assert!(matches!(
+        "[".repeat(100_000).parse::<Term>(),
+        Err(ParseError::TooDeep { .. })
+    ));
+    assert!(nested("<<", ">>", 1000).parse::<Term>().is_err());
+    assert_eq!(
+        Ok(Term::from(Binary::from(vec![1]))),
+        "<<<<<<1>>>>>>".parse::<Term>()
+    );
+}
+
+#[test]
+fn pretty_test() {
+    let term: Term = concat!(
+        "{state,#{\"name\" => <<\"alice\">>,age => 42,roles => [admin,developer,tester]},",
+        "[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20],\"hello\",[{a,1}|b]}"
+    )
+    .parse()
+    .unwrap();
+
+    // Width
+    assert_eq!(
+        concat!(
+            "{state,#{\"name\" => <<\"alice\">>,age => 42,roles => [admin,developer,tester]},\n",
+            "       [1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20],\n",
+            "       \"hello\",\n",
+            "       [{a,1}|b]}"
+        ),
+        term.pretty().to_string()
+    );
+    assert_eq!(
+        concat!(
+            "{state,#{\"name\" => <<\"alice\">>,\n",
+            "         age => 42,\n",
+            "         roles => [admin,developer,\n",
+            "                   tester]},\n",
+            "       [1,2,3,4,5,6,7,8,9,10,11,12,13,\n",
+            "        14,15,16,17,18,19,20],\n",
+            "       \"hello\",\n",
+            "       [{a,1}|b]}"
+        ),
+        term.pretty().width(40).to_string()
+    );
+
+    // Limits
+    assert_eq!(
+        "{state,#{\"name\" => <<\"alice\">>,...},...}",
+        term.pretty().depth(3).to_string()
+    );
+    assert_eq!(
+        "{state,#{\"name\" => <...",
+        term.pretty().chars_limit(20).to_string()
+    );
+    let tuple: Term = "{a,{b,c}}".parse().unwrap();
+    assert_eq!("...", tuple.pretty().width(2).depth(0).to_string());
+    assert_eq!("{...}", tuple.pretty().width(2).depth(1).to_string());
+}
+
+#[test]
+fn inspect_test() {
+    let inspect = |s: &str| s.parse::<Term>().unwrap().inspect().to_string();
+
+    // Atoms
+    assert_eq!(":foo", inspect("foo"));
+    assert_eq!(":\"Hello world\"", inspect("'Hello world'"));
+    assert_eq!("nil", inspect("nil"));
+    assert_eq!("true", inspect("true"));
+    assert_eq!("MyApp.User", inspect("'Elixir.MyApp.User'"));
+    assert_eq!(":valid?", inspect("'valid?'"));
+
+    // Numbers, pids and funs
+    assert_eq!("[-1, 2.5, 1.0e20]", inspect("[-1,2.5,1.0e20]"));
+    assert_eq!("#PID<nonode@nohost.1.2>", inspect("<nonode@nohost.1.2>"));
+    assert_eq!(
+        "#Reference<nonode@nohost.4.5>",
+        inspect("#Ref<nonode@nohost.4.5>")
+    );
+    assert_eq!("&:lists.map/2", inspect("fun lists:map/2"));
+    assert_eq!("&Enum.map/2", inspect("fun 'Elixir.Enum':map/2"));
+
+    // Binaries
+    assert_eq!("\"héllo \\#{x}\"", inspect("<<\"héllo #{x}\"/utf8>>"));
+    assert_eq!("<<0, 255>>", inspect("<<0,255>>"));
+    assert_eq!("<<1, 2::size(3)>>", inspect("<<1,2:3>>"));
+
+    // Lists
+    assert_eq!("~c\"abc\"", inspect("\"abc\""));
+    assert_eq!("[1000]", inspect("[1000]"));
+    assert_eq!("[a: 1, \"b c\": 2]", inspect("[{a,1},{'b c',2}]"));
+    assert_eq!("[1, 2 | 3]", inspect("[1,2|3]"));
+
+    // Maps and structs
+    assert_eq!("%{}", inspect("#{}"));
+    assert_eq!(
+        "%{:a => 1, \"b\" => 2}",
+        inspect("#{a => 1,<<\"b\">> => 2}")
+    );
+    let user: Term = "#{'__struct__' => 'Elixir.User',name => <<\"a\">>}"
+        .parse()
+        .unwrap();
+    assert_eq!("%User{name: \"a\"}", user.inspect().to_string());
+    assert_eq!(
+        "%{__struct__: User, name: \"a\"}",
+        user.inspect().structs(false).to_string()
+    );
+}
+
+#[test]
+fn macro_test() {
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    assert_eq!(parse("{}"), eetf!({}));
+    assert_eq!(parse("[]"), eetf!([]));
+    assert_eq!(parse("#{}"), eetf!(#{}));
+    assert_eq!(parse("<<>>"), eetf!(<<>>));
+    assert_eq!(
+        parse("{ok,true,-1,2.5,$a,\"ab\",10000000000,-10000000000}"),
+        eetf!({ok, true, -1, 2.5, 'a', "ab", 10_000_000_000i64, -10_000_000_000i64})
+    );
+    assert_eq!(
+        parse("[<<1,2>>,#{<<\"k\">> => -1,a => #{}},[x|<<\"y\">>]]"),
+        eetf!([<<1, 2>>, #{<<"k">> => -1, a => #{}}, [x | <<"y">>]])
+    );
+    assert_eq!(parse("[1,[2|3]]"), eetf!([1, [2 | 3],]));
+
+    // Splices
+    let bytes = vec![1, 2];
+    let pid = Pid::from(("nonode@nohost", 1, 2));
+    assert_eq!(
+        parse("{<<0,1,2,\"ab\">>,<nonode@nohost.1.2>}"),
+        eetf!({<<0, (&bytes), (&String::from("ab"))>>, (pid)})
+    );
+}
+
+#[test]
+fn conversion_test() {
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    // Native types into terms
+    assert_eq!(parse("-1"), Term::from(-1i64));
+    assert_eq!(parse("18446744073709551615"), Term::from(u64::MAX));
+    assert_eq!(parse("2.5"), Term::try_from(2.5).unwrap());
+    assert!(Term::try_from(f64::NAN).is_err());
+    assert_eq!(parse("true"), Term::from(true));
+    assert_eq!(parse("<<\"abc\">>"), Term::from(String::from("abc")));
+    assert_eq!(parse("[1,2]"), Term::from(vec![1i64, 2]));
+    assert_eq!(parse("undefined"), Term::from(None::<i64>));
+    assert_eq!(
+        parse("{ok,[a],3}"),
+        Term::from((Atom::from("ok"), vec![Atom::from("a")], 3u8))
+    );
+    let mut map = HashMap::new();
+    map.insert(String::from("k"), Some(1i64));
+    assert_eq!(parse("#{<<\"k\">> => 1}"), Term::from(map.clone()));
+
+    // Terms into native types
+    assert_eq!(-1, i64::try_from(parse("-1")).unwrap());
+    assert_eq!(
+        u64::MAX,
+        u64::try_from(parse("18446744073709551615")).unwrap()
+    );
+    assert!(u64::try_from(parse("-1")).is_err());
+    assert!(u128::try_from(parse("-1")).is_err());
+    assert!(u32::try_from(parse("4294967296")).is_err());
+    assert_eq!(2.0, f64::try_from(parse("2")).unwrap());
+    assert!(!bool::try_from(parse("false")).unwrap());
+    assert!(bool::try_from(parse("0")).is_err());
+    assert_eq!(
+        String::from("abc"),
+        String::try_from(parse("\"abc\"")).unwrap()
+    );
+    assert_eq!(vec![1u8, 2], Vec::<u8>::try_from(parse("[1,2]")).unwrap());
+    assert_eq!(
+        None,
+        Option::<String>::try_from(parse("undefined")).unwrap()
+    );
+    assert_eq!(Some(3), Option::<u8>::try_from(parse("3")).unwrap());
+    let pair: (Atom, u8) = parse("{ok,3}").try_into().unwrap();
+    assert_eq!((Atom::from("ok"), 3), pair);
+    assert!(<(Atom, u8)>::try_from(parse("{ok,3,4}")).is_err());
+    assert_eq!(map, HashMap::try_from(parse("#{<<\"k\">> => 1}")).unwrap());
+}
+
+#[test]
+fn std_conversion_test() {
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    assert_eq!(
+        parse("{<<\"ok\">>,42,[1,2]}"),
+        Term::from(("ok", 42u64, vec![1u8, 2]))
+    );
+    assert_eq!(
+        parse("{1,2,3,4,5,6,7,8,9,10,11,12}"),
+        Term::from((1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12))
+    );
+    assert_eq!(
+        parse("[a,b]"),
+        Term::from(&[Atom::from("a"), Atom::from("b")][..])
+    );
+    assert_eq!(parse("[<<\"a\">>]"), Term::from(&["a"][..]));
+
+    // Integers are fixed or big depending on the value
+    assert_eq!(parse("1"), Term::from(1i128));
+    assert_eq!(
+        parse("-170141183460469231731687303715884105728"),
+        Term::from(i128::MIN)
+    );
+    assert_eq!(
+        parse("340282366920938463463374607431768211455"),
+        Term::from(u128::MAX)
+    );
+    assert_eq!(
+        u128::MAX,
+        u128::try_from(parse("340282366920938463463374607431768211455")).unwrap()
+    );
+    assert!(i128::try_from(parse("340282366920938463463374607431768211455")).is_err());
+
+    let mut tree = std::collections::BTreeMap::new();
+    tree.insert(String::from("a"), vec![1i64]);
+    tree.insert(String::from("b"), vec![]);
+    let term = Term::from(tree.clone());
+    assert_eq!(parse("#{<<\"a\">> => [1],<<\"b\">> => []}"), term);
+    assert_eq!(tree, std::collections::BTreeMap::try_from(term).unwrap());
+
+    let mut map: HashMap<i64, bool, std::collections::hash_map::RandomState> = HashMap::default();
+    map.insert(1, true);
+    assert_eq!(parse("#{1 => true}"), Term::from(map.clone()));
+    assert_eq!(map, HashMap::try_from(parse("#{1 => true}")).unwrap());
+    assert_eq!(parse("undefined"), Term::from(None::<&str>));
+}
+
+#[test]
+fn proplist_test() {
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let term = parse("[{port,8080},debug,{port,8081},{<<\"k\">>,v},{name},42,{debug,false}]");
+    let list: &List = term.try_as_ref().unwrap();
+    let props = list.as_proplist();
+
+    assert_eq!(Some(&parse("8080")), props.get("port"));
+    assert_eq!(Some(&parse("true")), props.get(Atom::from("debug")));
+    assert_eq!(Some(&parse("v")), props.get(parse("<<\"k\">>")));
+    assert_eq!(None, props.get("name"));
+    assert_eq!(None, props.get("missing"));
+
+    assert_eq!(vec![&parse("8080"), &parse("8081")], props.get_all("port"));
+    assert_eq!(
+        vec![&parse("true"), &parse("false")],
+        props.get_all("debug")
+    );
+    assert!(props.get_bool("debug"));
+    assert!(!props.get_bool("port"));
+    assert!(!props.get_bool("missing"));
+
+    assert_eq!(
+        vec![
+            &parse("port"),
+            &parse("debug"),
+            &parse("<<\"k\">>"),
+            &parse("name")
+        ],
+        props.keys()
+    );
+
+    let map = props.to_map();
+    assert_eq!(
+        parse("#{port => 8080,debug => true,<<\"k\">> => v}"),
+        Term::from(map.clone())
+    );
+    assert_eq!(
+        parse("[{port,8080},{debug,true},{<<\"k\">>,v}]"),
+        Term::from(proplist::Proplist::from_map(&map))
+    );
+}
+
+#[test]
+fn record_test() {
+    use eetf::record::{Record, RecordError};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let hrl = r#"
+%% Records shared with the Erlang side.
+-include_lib("kernel/include/file.hrl").
+
+-record(user, {
+    id :: integer(),
+    name = <<"anon">> :: binary(), % the display name
+    roles = [admin, {group, "a,b"}] :: [atom() | tuple()],
+    created = erlang:system_time() :: integer(),
+    'e-mail',
+    flags = #{debug => false}
+}).
+-define(LIMIT, 10).
+-record(empty, {}).
+"#;
+    let records = Record::parse_hrl(hrl).unwrap();
+    assert_eq!(2, records.len());
+    let user = &records[0];
+    assert_eq!(
+        Record::new("user")
+            .field("id")
+            .field_with_default("name", parse("<<\"anon\">>"))
+            .field_with_default("roles", parse("[admin,{group,\"a,b\"}]"))
+            .field("created")
+            .field("e-mail")
+            .field_with_default("flags", parse("#{debug => false}")),
+        *user
+    );
+    assert_eq!(Record::new("empty"), records[1]);
+    match Record::parse_hrl("\n-record(user, {id").unwrap_err() {
+        RecordError::Syntax { line, .. } => assert_eq!(2, line),
+        e => panic!("{}", e),
+    }
+    assert!(Record::parse_hrl("-record(user, id).").is_err());
+
+    // Views
+    let term = parse("{user,1,<<\"alice\">>,[],0,\"a@b\",#{}}");
+    let tuple: &Tuple = term.try_as_ref().unwrap();
+    let view = user.view(tuple).unwrap();
+    assert_eq!(Some(&parse("1")), view.get("id"));
+    assert_eq!(None, view.get("age"));
+    assert_eq!(1, view.get_into::<i64>("id").unwrap());
+    assert_eq!("a@b", view.get_into::<String>("e-mail").unwrap());
+    assert!(view.get_as::<List>("roles").unwrap().is_nil());
+    assert!(view.get_as::<Atom>("roles").is_err());
+    assert!(view.get_into::<u8>("name").is_err());
+    assert!(view.get_into::<u8>("age").is_err());
+    assert_eq!(
+        vec!["id", "name", "roles", "created", "e-mail", "flags"],
+        view.iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+    for s in &["{user,1}", "{account,1,2,3,4,5,6}", "{}"] {
+        let term = parse(s);
+        assert!(user.view(term.try_as_ref().unwrap()).is_err());
+    }
+
+    // Building
+    assert_eq!(
+        parse("{user,7,<<\"bob\">>,[admin,{group,\"a,b\"}],undefined,undefined,#{debug => false}}"),
+        Term::from(
+            user.build(vec![
+                ("id", Term::from(7i64)),
+                ("name", Term::from(String::from("bob")))
+            ])
+            .unwrap()
+        )
+    );
+    assert!(user.build(vec![("age", 1i64)]).is_err());
+}
+
+#[test]
+fn elixir_struct_test() {
+    use eetf::elixir::StructBuilder;
+    use eetf::pattern::ElixirStruct;
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let term =
+        parse("#{'__struct__' => 'Elixir.MyApp.User',id => 1,name => <<\"a\">>,<<\"x\">> => 2}");
+    let map: &Map = term.try_as_ref().unwrap();
+    let user = map.as_elixir_struct().unwrap();
+    assert_eq!("MyApp.User", user.module());
+    assert_eq!("Elixir.MyApp.User", user.module_atom().name);
+    assert_eq!(Some(&parse("1")), user.get("id"));
+    assert_eq!(None, user.get("__struct__"));
+    assert_eq!(1, user.get_into::<u8>("id").unwrap());
+    assert_eq!(None, user.get_into::<Option<u8>>("age").unwrap());
+    assert!(user.get_into::<u8>("age").is_err());
+    assert_eq!(
+        vec!["id", "name"],
+        user.fields().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+
+    let plain = parse("#{id => 1}");
+    let plain: &Map = plain.try_as_ref().unwrap();
+    assert!(plain.as_elixir_struct().is_none());
+
+    // Building
+    assert_eq!(
+        parse("#{'__struct__' => 'Elixir.MyApp.User',id => 1,name => <<\"a\">>}"),
+        Term::from(
+            StructBuilder::new("MyApp.User")
+                .field("id", 1)
+                .field("name", String::from("a"))
+                .build()
+        )
+    );
+    assert_eq!(
+        parse("#{'__struct__' => 'Elixir.Range'}"),
+        Term::from(StructBuilder::new("Elixir.Range").build())
+    );
+
+    // Patterns
+    let user = term.as_match(ElixirStruct("MyApp.User")).unwrap();
+    assert_eq!(Some(&parse("<<\"a\">>")), user.get("name"));
+    assert!(term.as_match(ElixirStruct("MyApp.Admin")).is_err());
+    assert!(parse("{}").as_match(ElixirStruct("MyApp.User")).is_err());
+}
+
+#[test]
+fn map_pattern_test() {
+    use eetf::pattern::{any, MapWith, Optional, I32, U8};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let term = parse("#{id => 1,name => <<\"a\">>,<<\"k\">> => {x,-2},extra => y}");
+
+    let (id, name) = term
+        .as_match(MapWith((("id", U8), ("name", any::<Binary>()))))
+        .unwrap();
+    assert_eq!(1, id);
+    assert_eq!(b"a", &name.bytes[..]);
+
+    let (k, age, id) = term
+        .as_match(MapWith((
+            (parse("<<\"k\">>"), ("x", I32)),
+            ("age", Optional(U8)),
+            (Atom::from("id"), Optional(U8)),
+        )))
+        .unwrap();
+    assert_eq!(("x", -2), k);
+    assert_eq!(None, age);
+    assert_eq!(Some(1), id);
+
+    // Missing required keys
+    assert!(term.as_match(MapWith((("age", U8),))).is_err());
+    // Values which do not match, even for optional entries
+    assert!(term.as_match(MapWith((("name", U8),))).is_err());
+    assert!(term.as_match(MapWith((("name", Optional(U8)),))).is_err());
+    // Not a map
+    assert!(parse("[]").as_match(MapWith((("id", U8),))).is_err());
+}
+
+#[test]
+fn bin_pattern_test() {
+    use eetf::pattern::{Bin, BinarySeg, FloatSeg, IntSeg, RestSeg, Size, UintSeg};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    let term = parse("<<1,2,-2:16,-3:32/little,1.5/float,2.5:32/float-little>>");
+    let output = term
+        .as_match(Bin((
+            UintSeg::new(16),
+            IntSeg::new(16),
+            IntSeg::new(32).little(),
+            FloatSeg::new(64),
+            FloatSeg::new(32).little(),
+        )))
+        .unwrap();
+    assert_eq!((0x0102, -2, -3, 1.5, 2.5), output);
+    assert_eq!(
+        (0x0201, 0xFFFE),
+        term.as_match(Bin((UintSeg::new(16).little(), UintSeg::new(16), RestSeg)))
+            .map(|(a, b, _)| (a, b))
+            .unwrap()
+    );
+
+    // Sizes referring to earlier segments
+    let term = parse("<<3,\"abcde\">>");
+    let (_, data, rest) = term
+        .as_match(Bin((
+            UintSeg::new(8),
+            BinarySeg::new(Size::Field(0)),
+            RestSeg,
+        )))
+        .unwrap();
+    assert_eq!((&b"abc"[..], &b"de"[..]), (data, rest));
+    assert!(parse("<<9,\"abc\">>")
+        .as_match(Bin((
+            UintSeg::new(8),
+            BinarySeg::new(Size::Field(0)),
+            RestSeg
+        )))
+        .is_err());
+
+    // Bitstrings
+    let term = parse("<<5:3,1:1,-1:4,1:2>>");
+    assert_eq!(
+        (5, 1, -1, 1),
+        term.as_match(Bin((
+            UintSeg::new(3),
+            UintSeg::new(1),
+            IntSeg::new(4),
+            UintSeg::new(2)
+        )))
+        .unwrap()
+    );
+    assert!(term.as_match(Bin((UintSeg::new(8), RestSeg))).is_err());
+
+    // The segments must cover the whole input
+    assert!(parse("<<1,2>>").as_match(Bin((UintSeg::new(8),))).is_err());
+    assert!(parse("<<1>>").as_match(Bin((UintSeg::new(16),))).is_err());
+    assert!(parse("foo").as_match(Bin((RestSeg,))).is_err());
+
+    // A decoded bitstring without bytes has no bits, whatever its tail size
+    let term = Term::decode(Cursor::new(&[131, 77, 0, 0, 0, 0, 5])).unwrap();
+    assert!(term.as_match(Bin((UintSeg::new(5),))).is_err());
+    assert!(term.as_match(Bin((RestSeg,))).is_ok());
+    assert!(term
+        .as_match(eetf::pattern::bit_binary().max_bits(0))
+        .is_ok());
+}
+
+#[test]
+fn opaque_pattern_test() {
+    use eetf::pattern::{
+        binary, bit_binary, external_fun, internal_fun, pid, port, reference, Nil,
+    };
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let term = parse("{<foo@bar.1.2>,#Ref<foo@bar.1.2.3>,#Port<foo@bar.7>}");
+
+    let (p, r, _) = term.as_match((pid(), reference(), port())).unwrap();
+    assert_eq!((1, 2), (p.id, p.serial));
+    assert_eq!(vec![1, 2, 3], r.id);
+    let constrained = (
+        pid().node("foo@bar").id(1).serial(2).creation(0),
+        reference().node("foo@bar").id(vec![1, 2, 3]),
+        port().node("foo@bar").id(7),
+    );
+    assert!(term.as_match(constrained).is_ok());
+    assert!(term
+        .as_match((pid().serial(3), reference(), port()))
+        .is_err());
+    assert!(term
+        .as_match((pid(), reference().node("baz@qux"), port()))
+        .is_err());
+    assert!(term
+        .as_match((pid(), reference(), port().creation(1)))
+        .is_err());
+    assert!(parse("foo").as_match(pid()).is_err());
+
+    let fun = Term::from(ExternalFun::from(("lists", "map", 2)));
+    assert!(fun
+        .as_match(external_fun().module("lists").function("map").arity(2))
+        .is_ok());
+    assert!(fun.as_match(external_fun().arity(3)).is_err());
+    let fun = Term::from(InternalFun::New {
+        module: Atom::from("foo"),
+        arity: 1,
+        pid: Pid::new("nonode@nohost", 1, 2, 0),
+        free_vars: vec![],
+        index: 0,
+        uniq: [0; 16],
+        old_index: 0,
+        old_uniq: 0,
+    });
+    assert!(fun.as_match(internal_fun().module("foo").arity(1)).is_ok());
+    assert!(fun.as_match(internal_fun().module("bar")).is_err());
+    assert!(fun.as_match(Nil).is_err());
+
+    let term = parse("{<<\"abc\">>,<<1,2:3>>}");
+    assert!(term
+        .as_match((binary().len(3), bit_binary().bits(11)))
+        .is_ok());
+    assert!(term.as_match((binary().min_len(4), bit_binary())).is_err());
+    assert!(term.as_match((binary().max_len(2), bit_binary())).is_err());
+    assert!(term
+        .as_match((binary(), bit_binary().min_bits(12)))
+        .is_err());
+}
+
+#[test]
+fn guard_pattern_test() {
+    use eetf::pattern::{any, Eq, Not, OneOf, Pattern, Range, I64, U8};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    let even = I64.where_(|x| x % 2 == 0).named("even");
+    assert_eq!(4, parse("4").as_match(even.clone()).unwrap());
+    let three = parse("3");
+    let e = three.as_match(even.clone()).unwrap_err();
+    assert!(format!("{:?}", e.pattern).contains("even"));
+    assert!(parse("a").as_match(even).is_err());
+    let e = three.as_match(I64.where_(|x| *x > 3)).unwrap_err();
+    assert!(format!("{:?}", e.pattern).contains("closure"));
+
+    assert_eq!(
+        ("ok", 42),
+        parse("{ok,42}").as_match(("ok", Range(1..100))).unwrap()
+    );
+    assert!(parse("100").as_match(Range(1..100)).is_err());
+    assert!(parse("100").as_match(Range(1..=100)).is_ok());
+    assert!(parse("-1").as_match(Range(0u8..10)).is_err());
+    assert!(parse("1.5").as_match(Range(1.0..2.0)).is_ok());
+
+    let term = parse("{ok,[1]}");
+    assert_eq!(&term, term.as_match(Eq(parse("{ok,[1]}"))).unwrap());
+    assert!(term.as_match(Eq(parse("{ok,[2]}"))).is_err());
+
+    assert_eq!("b", parse("b").as_match(OneOf(["a", "b"])).unwrap());
+    assert!(parse("c").as_match(OneOf(vec!["a", "b"])).is_err());
+    assert!(parse("c").as_match(OneOf(Vec::<&str>::new())).is_err());
+
+    assert!(parse("c").as_match(Not(OneOf(["a", "b"]))).is_ok());
+    assert!(parse("a").as_match(Not("a")).is_err());
+
+    let (foo, one) = (parse("foo"), parse("1"));
+    let name = any::<Atom>().map(|a| a.name.to_uppercase());
+    assert_eq!("FOO", foo.as_match(name.clone()).unwrap());
+    assert!(one.as_match(name).is_err());
+    assert_eq!(Some(3), parse("3").as_match(U8.map(Some)).unwrap());
+}
+
+#[test]
+fn large_arity_pattern_test() {
+    use eetf::pattern::{any, FixList, Or, TupleOf, Union16, U8};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    let term = parse("{user,1,2,3,4,5,6,7,8,9,10,11}");
+    let (tag, .., last) = term
+        .as_match(("user", U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8))
+        .unwrap();
+    assert_eq!(("user", 11), (tag, last));
+
+    let term = parse("{user,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15}");
+    let output = term
+        .as_match(TupleOf((
+            "user", U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8,
+        )))
+        .unwrap();
+    assert_eq!(15, output.15);
+    assert!(term
+        .as_match(TupleOf((
+            "user", U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8
+        )))
+        .is_err());
+
+    let list = parse("[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16]");
+    let output = list
+        .as_match(FixList((
+            U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8, U8,
+        )))
+        .unwrap();
+    assert_eq!((1, 16), (output.0, output.15));
+
+    let or = Or((
+        "a",
+        "b",
+        "c",
+        "d",
+        "e",
+        "f",
+        "g",
+        "h",
+        "i",
+        "j",
+        "k",
+        "l",
+        "m",
+        "n",
+        "o",
+        any::<Tuple>(),
+    ));
+    match parse("{}").as_match(or.clone()).unwrap() {
+        Union16::P(t) => assert!(t.elements.is_empty()),
+        _ => panic!(),
+    }
+    assert!(matches!(
+        parse("o").as_match(or.clone()),
+        Ok(Union16::O("o"))
+    ));
+    let z = parse("z");
+    let e = z.as_match(or).unwrap_err();
+    assert!(format!("{:?}", e.pattern).starts_with("Or(\"a\", \"b\""));
+}
+
+#[test]
+fn unmatch_report_test() {
+    use eetf::pattern::{any, MapWith, Or, Step, VarList, U8};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    let term = parse("{ok,[#{name => <<\"a\">>,age => 1},#{name => <<\"b\">>,age => 300}]}");
+    let e = term
+        .as_match((
+            "ok",
+            VarList(MapWith((("name", any::<Binary>()), ("age", U8)))),
+        ))
+        .unwrap_err();
+    assert_eq!(
+        vec![Step::Index(1), Step::Index(1), Step::Key(&parse("age"))],
+        e.path().0
+    );
+    assert_eq!("$[1][1]#{age}", e.path().to_string());
+    assert_eq!("at $[1][1]#{age}: expected U8, found 300", e.to_string());
+
+    // Values are truncated
+    let term = parse("{1,2,3,4,5}");
+    let e = term.as_match(((),)).unwrap_err();
+    assert_eq!(
+        "at $: expected ((),), found {1,2,3...",
+        e.report().max_len(6).to_string()
+    );
+
+    // All the alternatives are listed
+    let term = parse("[{error,x}]");
+    let e = term
+        .as_match(VarList(Or((("ok", U8), "error"))))
+        .unwrap_err();
+    assert_eq!(3, e.depth());
+    assert_eq!(vec![Step::Index(0), Step::Index(0)], e.path().0);
+    let alternatives = e.cause.as_ref().unwrap().tried_alternatives();
+    assert_eq!(2, alternatives.len());
+    assert_eq!(parse("{error,x}"), *alternatives[1].input);
+    assert_eq!(
+        "at $[0]: no alternative matched {error,x}\n  \
+         - at $[0][0]: expected \"ok\", found error\n  \
+         - at $[0]: expected \"error\", found {error,x}",
+        e.to_string()
+    );
+}
+
+#[test]
+fn into_match_test() {
+    use eetf::pattern::{
+        any, Bin, BinarySeg, Or, OwnedUnmatch, Pattern, Prefix, RestSeg, Union2, VarList, U8,
+    };
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    fn bytes_ptr(t: &Term) -> *const u8 {
+        match *t {
+            Term::Binary(ref x) => x.bytes.as_ptr(),
+            _ => std::ptr::null(),
+        }
+    }
+
+    let (name, ids, reason) = parse("{user,<<\"a\">>,[1,2],{error,timeout}}")
+        .into_match((
+            "user",
+            any::<Binary>(),
+            VarList(U8.where_(|x| *x > 0)),
+            Or(("ok", ("error", any::<Term>()))),
+        ))
+        .map(|(_, name, ids, reason)| (name, ids, reason))
+        .unwrap();
+    assert_eq!(Binary::from(&b"a"[..]), name);
+    assert_eq!(vec![1, 2], ids);
+    match reason {
+        Union2::B((_, reason)) => assert_eq!(parse("timeout"), reason),
+        Union2::A(_) => panic!(),
+    }
+
+    // Sub-terms are moved out of the term
+    let term = parse("[<<\"x\">>,<<\"y\">>,<<\"z\">>|t]");
+    let bytes = |t: &Term| match *t {
+        Term::List(ref x) => x.elements.iter().map(bytes_ptr).collect::<Vec<_>>(),
+        Term::ImproperList(ref x) => x.elements.iter().map(bytes_ptr).collect(),
+        _ => unreachable!(),
+    };
+    let ptrs = bytes(&term);
+    let ((x,), tail) = term.into_match(Prefix((any::<Binary>(),))).unwrap();
+    assert_eq!(ptrs[0], x.bytes.as_ptr());
+    assert_eq!(parse("[<<\"y\">>,<<\"z\">>|t]"), tail);
+    assert_eq!(ptrs[1..], bytes(&tail)[..]);
+
+    let (head, rest) = parse("<<1,2,3>>")
+        .into_match(Bin((BinarySeg::new(1), RestSeg)))
+        .unwrap();
+    assert_eq!((vec![1], vec![2, 3]), (head, rest));
+
+    let e = parse("{user,[1,300]}")
+        .into_match(("user", VarList(U8)))
+        .unwrap_err();
+    let e = std::thread::spawn(move || e).join().unwrap();
+    assert_eq!(
+        OwnedUnmatch {
+            input: Box::new(parse("300")),
+            pattern: "U8".to_owned(),
+            path: "$[1][1]".to_owned(),
+            depth: 2,
+            report: "at $[1][1]: expected U8, found 300".to_owned(),
+        },
+        e
+    );
+    assert_eq!(e.report, e.to_string());
+
+    let term = parse("foo");
+    assert_eq!(
+        term.as_match(U8).unwrap_err().into_owned(),
+        term.into_match(U8).unwrap_err()
+    );
+}
+
+#[test]
+fn spec_test() {
+    use eetf::pattern::{
+        any, binary, pid, Bin, BinarySeg, Cons, ElixirStruct, Eq, FixList, MapWith, Nil, Not,
+        OneOf, Optional, Or, Pattern, Range, RestSeg, Str, UintSeg, Unicode, VarList, F64, I64, U8,
+    };
+    use eetf::spec::{MapEntrySpec, Spec};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+
+    let pattern = Or((
+        (
+            "ok",
+            MapWith((("id", U8), ("name", Optional(any::<Binary>())))),
+        ),
+        ("error", any::<Atom>()),
+    ));
+    assert_eq!(
+        Spec::Union(vec![
+            Spec::Tuple(vec![
+                Spec::Atom("ok".to_owned()),
+                Spec::Map(vec![
+                    MapEntrySpec {
+                        key: Spec::Atom("id".to_owned()),
+                        value: Spec::Integer {
+                            min: Some(0),
+                            max: Some(255)
+                        },
+                        required: true,
+                    },
+                    MapEntrySpec {
+                        key: Spec::Atom("name".to_owned()),
+                        value: Spec::Bits { size: 0, unit: 8 },
+                        required: false,
+                    },
+                    MapEntrySpec {
+                        key: Spec::Any,
+                        value: Spec::Any,
+                        required: false,
+                    },
+                ]),
+            ]),
+            Spec::Tuple(vec![
+                Spec::Atom("error".to_owned()),
+                Spec::Builtin("atom()")
+            ]),
+        ]),
+        pattern.spec()
+    );
+    assert_eq!(
+        "{ok, #{id := 0..255, name => binary(), term() => term()}} | {error, atom()}",
+        pattern.spec().to_string()
+    );
+
+    let spec = |p: &dyn Fn() -> Spec| p().to_string();
+    assert_eq!("{}", spec(&|| ().spec()));
+    assert_eq!("[]", spec(&|| Nil.spec()));
+    assert_eq!("[number()]", spec(&|| VarList(F64).spec()));
+    assert_eq!(
+        "nonempty_list(a | b)",
+        spec(&|| FixList(("a", "b", "a")).spec())
+    );
+    assert_eq!(
+        "nonempty_list(pid() | term())",
+        spec(&|| Cons(pid(), any::<Term>()).spec())
+    );
+    assert_eq!("[char()]", spec(&|| Str(Unicode).spec()));
+    assert_eq!("1..99", spec(&|| Range(1..100).spec()));
+    assert_eq!("number()", spec(&|| Range(0.0..=1.0).spec()));
+    assert_eq!(
+        "-9223372036854775808..9223372036854775807",
+        spec(&|| I64.where_(|x| *x > 0).spec())
+    );
+    assert_eq!(
+        "'Foo' | <<_:8>> | 1 | float() | {a, nonempty_list(1)}",
+        spec(&|| OneOf(vec![
+            Eq(parse("'Foo'")),
+            Eq(parse("<<\"x\">>")),
+            Eq(parse("1")),
+            Eq(parse("1.5")),
+            Eq(parse("{a,[1]}"))
+        ])
+        .spec())
+    );
+    assert_eq!("none()", spec(&|| OneOf(Vec::<U8>::new()).spec()));
+    assert_eq!("term()", spec(&|| Not(U8).spec()));
+    assert_eq!("<<_:128>>", spec(&|| binary().len(16).spec()));
+    assert_eq!(
+        "<<_:16, _:_*8>>",
+        spec(&|| Bin((UintSeg::new(8), UintSeg::new(8), RestSeg)).spec())
+    );
+    assert_eq!(
+        "<<_:8, _:_*8>>",
+        spec(&|| Bin((UintSeg::new(8), BinarySeg::new(pattern::Size::Field(0)))).spec())
+    );
+    assert_eq!(
+        "#{'__struct__' := 'Elixir.MyApp.User', term() => term()}",
+        spec(&|| ElixirStruct("MyApp.User").spec())
+    );
+}
+
+#[test]
+fn query_test() {
+    use eetf::query::{Query, Selector};
+
+    let parse = |s: &str| s.parse::<Term>().unwrap();
+    let query = |term: &Term, q: &str| -> Vec<String> {
+        term.query(q)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.to_string())
+            .collect()
+    };
+
+    let term = parse(
+        "{response,200,#{users => [#{<<\"name\">> => <<\"a\">>,age => 1},\
+         #{<<\"name\">> => <<\"b\">>}],status => ok,'Quoted Key' => 1},\
+         [{id,1},{id,2},debug]}",
+    );
+    assert_eq!(vec![term.to_string()], query(&term, ""));
+    assert_eq!(vec!["200"], query(&term, ".1"));
+    assert_eq!(vec!["200"], query(&term, "[-3]"));
+    assert_eq!(vec!["<<97>>", "<<98>>"], query(&term, ".2.users[*].#name"));
+    assert_eq!(vec!["1"], query(&term, ".2.users[*].age"));
+    assert_eq!(vec!["ok"], query(&term, ".2#{status}"));
+    assert_eq!(vec!["ok"], query(&term, "[2].status"));
+    assert_eq!(vec!["1"], query(&term, ".2.'Quoted Key'"));
+    assert_eq!(vec!["1"], query(&term, ".3.id"));
+    assert_eq!(vec!["response", "200"], query(&term, "[0..2]"));
+    assert_eq!(vec!["{id,2}", "debug"], query(&term, ".3[1..]"));
+    assert_eq!(vec!["{id,1}"], query(&term, ".3[..-2]"));
+    assert_eq!(3, term.query(".2.*").unwrap().len());
+    assert!(query(&term, ".5").is_empty());
+    assert!(query(&term, ".0.foo[*]").is_empty());
+    assert!(query(&term, "[10..]").is_empty());
+
+    let term = parse("#{<<\"id\">> => 1,{a,b} => 2}");
+    assert_eq!(vec!["1"], query(&term, "#{<<\"id\">>}"));
+    assert_eq!(vec!["2"], query(&term, "#{ {a,b} }"));
+
+    assert_eq!(
+        Query(vec![
+            Selector::Index(2),
+            Selector::Key(parse("users")),
+            Selector::All,
+            Selector::Range(Some(-2), None),
+            Selector::Key(parse("<<\"name\">>")),
+        ]),
+        ".2.users[*][-2..].#name".parse().unwrap()
+    );
+    assert_eq!(
+        Err(ParseError::UnexpectedChar {
+            position: 0,
+            found: 'x'
+        }),
+        "x".parse::<Query>()
+    );
+    assert_eq!(Err(ParseError::UnexpectedEof), "[1".parse::<Query>());
+    assert_eq!(
+        Err(ParseError::UnexpectedChar {
+            position: 1,
+            found: ']'
+        }),
+        "[]".parse::<Query>()
+    );
+    assert!(term.query("#{a").is_err());
+
+    let mut term = parse("{[1,2,3],#{a => 1,b => 2},[{k,v}]}");
+    for x in term.query_mut(".1.*").unwrap() {
+        *x = Term::from(FixInteger::from(0));
+    }
+    for x in term.query_mut(".0[1..]").unwrap() {
+        *x = Term::from(FixInteger::from(9));
+    }
+    *term.query_mut(".2.k").unwrap()[0] = parse("w");
+    assert_eq!(parse("{[1,9,9],#{a => 0,b => 0},[{k,w}]}"), term);
+}
+
 fn encode(term: Term) -> Vec<u8> {
     let mut buf = Vec::new();
     term.encode(&mut buf).unwrap();
@@ -0,0 +1,139 @@
+#![cfg(feature = "msgpack")]
+extern crate eetf;
+extern crate rmpv;
+
+use eetf::msgpack;
+use eetf::*;
+use rmpv::Value;
+
+fn term(s: &str) -> Term {
+    s.parse().unwrap()
+}
+
+fn round_trip(t: Term) -> Term {
+    msgpack::from_slice(&msgpack::to_vec(&t).unwrap()).unwrap()
+}
+
+fn term_ext(t: &Term) -> Value {
+    let mut buf = Vec::new();
+    t.encode(&mut buf).unwrap();
+    Value::Ext(msgpack::TERM_EXT, buf)
+}
+
+#[test]
+fn to_value_test() {
+    assert_eq!(
+        Value::Array(vec![
+            Value::Nil,
+            Value::Boolean(true),
+            Value::from(1),
+            Value::from(10_000_000_000i64),
+            Value::F64(2.5),
+            Value::from("abc"),
+            Value::Binary(vec![255]),
+        ]),
+        msgpack::to_value(&term("[null,true,1,10000000000,2.5,<<\"abc\">>,<<255>>]")).unwrap()
+    );
+    assert_eq!(
+        Value::Ext(msgpack::ATOM_EXT, b"foo".to_vec()),
+        msgpack::to_value(&term("foo")).unwrap()
+    );
+    assert_eq!(
+        Value::Ext(msgpack::ATOM_EXT, b"nil".to_vec()),
+        msgpack::to_value(&term("nil")).unwrap()
+    );
+    assert_eq!(
+        Value::Map(vec![(Value::from("a"), Value::Array(vec![]))]),
+        msgpack::to_value(&term("#{<<\"a\">> => []}")).unwrap()
+    );
+}
+
+#[test]
+fn from_value_test() {
+    assert_eq!(
+        term("[null,false,-1,18446744073709551615,<<\"a\">>,<<1,2>>]"),
+        msgpack::from_value(&Value::Array(vec![
+            Value::Nil,
+            Value::Boolean(false),
+            Value::from(-1),
+            Value::from(u64::MAX),
+            Value::from("a"),
+            Value::Binary(vec![1, 2]),
+        ]))
+        .unwrap()
+    );
+    assert!(msgpack::from_slice(&[0x92, 0x01]).is_err());
+}
+
+#[test]
+fn float_test() {
+    assert_eq!(term("1.5"), msgpack::from_value(&Value::F32(1.5)).unwrap());
+    assert_eq!(term("1.5"), msgpack::from_value(&Value::F64(1.5)).unwrap());
+    for x in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert!(msgpack::from_value(&Value::F64(*x)).is_err());
+        assert!(msgpack::from_value(&Value::F32(*x as f32)).is_err());
+    }
+    // float 64: NaN
+    assert!(msgpack::from_slice(&[0xcb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn integer_boundary_test() {
+    // Integers in the 64-bit range are native MessagePack integers
+    for (s, value) in &[
+        ("-9223372036854775808", Value::from(i64::MIN)),
+        ("18446744073709551615", Value::from(u64::MAX)),
+    ] {
+        assert_eq!(*value, msgpack::to_value(&term(s)).unwrap(), "{}", s);
+        assert_eq!(term(s), msgpack::from_value(value).unwrap(), "{}", s);
+    }
+
+    // The others are written in the External Term Format
+    for s in &[
+        "-9223372036854775809",
+        "18446744073709551616",
+        "-18446744073709551616",
+        "123456789012345678901234567890",
+    ] {
+        let value = msgpack::to_value(&term(s)).unwrap();
+        assert_eq!(term_ext(&term(s)), value, "{}", s);
+        assert_eq!(term(s), round_trip(term(s)), "{}", s);
+    }
+}
+
+#[test]
+fn extension_test() {
+    for s in &[
+        "'hello world'",
+        "{ok,{1,2},{}}",
+        "[1,2|3]",
+        "<<1,2:3>>",
+        "<nonode@nohost.1.2>",
+        "#Ref<nonode@nohost.1.2.3>",
+        "#Port<nonode@nohost.3>",
+        "fun lists:map/2",
+    ] {
+        assert_eq!(term(s), round_trip(term(s)), "{}", s);
+    }
+
+    // Extension types of other applications, and the timestamp type (-1)
+    for ty in &[-1, 0, 6, 100] {
+        assert!(
+            msgpack::from_value(&Value::Ext(*ty, vec![1])).is_err(),
+            "{}",
+            ty
+        );
+    }
+    // Malformed payloads of the types used here
+    for (ty, data) in &[
+        (msgpack::ATOM_EXT, vec![255]),
+        (msgpack::TUPLE_EXT, vec![0x01]),
+        (msgpack::IMPROPER_LIST_EXT, vec![0x91, 0x01]),
+        (msgpack::BITSTRING_EXT, vec![9, 1]),
+        (msgpack::BITSTRING_EXT, vec![3]),
+        (msgpack::TERM_EXT, vec![131, 255]),
+    ] {
+        let value = Value::Ext(*ty, data.clone());
+        assert!(msgpack::from_value(&value).is_err(), "{:?}", value);
+    }
+}
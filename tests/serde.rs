@@ -0,0 +1,349 @@
+#![cfg(feature = "serde")]
+extern crate eetf;
+extern crate serde;
+
+use eetf::de;
+use eetf::ser::{self, NoneRepr, Serializer, StructRepr};
+use eetf::*;
+use serde::de::{self as serde_de, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Cursor;
+
+#[derive(Serialize)]
+#[serde(rename = "user")]
+struct User {
+    id: u64,
+    name: String,
+    tags: Vec<&'static str>,
+    manager: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Event {
+    Ping,
+    Login(u32),
+    Move(i32, i32),
+    Rename { from: String, to: String },
+}
+
+#[derive(Serialize)]
+struct Meters(f64);
+
+#[derive(Serialize)]
+#[serde(rename = "point")]
+struct Point {
+    x: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    y: i32,
+}
+
+fn term(s: &str) -> Term {
+    s.parse().unwrap()
+}
+
+#[test]
+fn serialize_primitives_test() {
+    assert_eq!(term("true"), ser::to_term(&true).unwrap());
+    assert_eq!(term("-1"), ser::to_term(&-1i8).unwrap());
+    assert_eq!(term("4294967295"), ser::to_term(&u32::MAX).unwrap());
+    assert_eq!(
+        term("-170141183460469231731687303715884105728"),
+        ser::to_term(&i128::MIN).unwrap()
+    );
+    assert_eq!(term("1.5"), ser::to_term(&1.5f32).unwrap());
+    assert_eq!(term("$a"), ser::to_term(&'a').unwrap());
+    assert_eq!(term("<<\"abc\">>"), ser::to_term("abc").unwrap());
+    assert_eq!(term("{}"), ser::to_term(&()).unwrap());
+    assert_eq!(term("2.5"), ser::to_term(&Meters(2.5)).unwrap());
+    assert_eq!(term("undefined"), ser::to_term(&None::<u8>).unwrap());
+    assert_eq!(
+        term("nil"),
+        None::<u8>
+            .serialize(Serializer::new().none_repr(NoneRepr::Nil))
+            .unwrap()
+    );
+    assert!(ser::to_term(&f64::NAN).is_err());
+}
+
+#[test]
+fn serialize_compound_test() {
+    assert_eq!(
+        term("{[1,2],{<<\"a\">>,$b}}"),
+        ser::to_term(&(vec![1, 2], ("a", 'b'))).unwrap()
+    );
+
+    let mut map = BTreeMap::new();
+    map.insert("k", vec![Some(1), None]);
+    assert_eq!(
+        term("#{<<\"k\">> => [1,undefined]}"),
+        ser::to_term(&map).unwrap()
+    );
+
+    let user = User {
+        id: 1,
+        name: "alice".to_owned(),
+        tags: vec!["admin"],
+        manager: None,
+    };
+    assert_eq!(
+        term("#{id => 1,name => <<\"alice\">>,tags => [<<\"admin\">>],manager => undefined}"),
+        ser::to_term(&user).unwrap()
+    );
+    assert_eq!(
+        term("{user,1,<<\"alice\">>,[<<\"admin\">>],undefined}"),
+        user.serialize(Serializer::new().struct_repr(StructRepr::Record))
+            .unwrap()
+    );
+
+    // Skipped fields keep their positions in records
+    let point = Point {
+        x: 1,
+        label: None,
+        y: 2,
+    };
+    assert_eq!(term("#{x => 1,y => 2}"), ser::to_term(&point).unwrap());
+    assert_eq!(
+        term("{point,1,undefined,2}"),
+        point
+            .serialize(Serializer::new().struct_repr(StructRepr::Record))
+            .unwrap()
+    );
+}
+
+#[test]
+fn serialize_enum_test() {
+    assert_eq!(term("ping"), ser::to_term(&Event::Ping).unwrap());
+    assert_eq!(term("{login,7}"), ser::to_term(&Event::Login(7)).unwrap());
+    assert_eq!(
+        term("{move,1,-1}"),
+        ser::to_term(&Event::Move(1, -1)).unwrap()
+    );
+
+    let rename = Event::Rename {
+        from: "a".to_owned(),
+        to: "b".to_owned(),
+    };
+    assert_eq!(
+        term("{rename,#{from => <<\"a\">>,to => <<\"b\">>}}"),
+        ser::to_term(&rename).unwrap()
+    );
+    assert_eq!(
+        term("{rename,<<\"a\">>,<<\"b\">>}"),
+        rename
+            .serialize(Serializer::new().struct_repr(StructRepr::Record))
+            .unwrap()
+    );
+}
+
+#[test]
+fn serialize_to_vec_test() {
+    let bytes = ser::to_vec(&Event::Login(7)).unwrap();
+    assert_eq!(
+        term("{login,7}"),
+        Term::decode(Cursor::new(&bytes)).unwrap()
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename = "user")]
+struct Account {
+    id: u64,
+    name: String,
+    admin: bool,
+    email: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Command {
+    Ping,
+    Login(u32),
+    Move(i32, i32),
+    Rename { from: String, to: String },
+}
+
+/// An integer with the visitor method `deserialize_any` called for it.
+#[derive(Debug, PartialEq)]
+enum Number {
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+}
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NumberVisitor;
+        impl<'de> Visitor<'de> for NumberVisitor {
+            type Value = Number;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an integer")
+            }
+            fn visit_i64<E: serde_de::Error>(self, v: i64) -> Result<Number, E> {
+                Ok(Number::I64(v))
+            }
+            fn visit_u64<E: serde_de::Error>(self, v: u64) -> Result<Number, E> {
+                Ok(Number::U64(v))
+            }
+            fn visit_i128<E: serde_de::Error>(self, v: i128) -> Result<Number, E> {
+                Ok(Number::I128(v))
+            }
+            fn visit_u128<E: serde_de::Error>(self, v: u128) -> Result<Number, E> {
+                Ok(Number::U128(v))
+            }
+        }
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+#[test]
+fn deserialize_primitives_test() {
+    assert!(de::from_term::<bool>(&term("true")).unwrap());
+    assert_eq!(-1, de::from_term::<i8>(&term("-1")).unwrap());
+    assert_eq!(
+        u64::MAX,
+        de::from_term::<u64>(&term("18446744073709551615")).unwrap()
+    );
+    assert!(de::from_term::<u64>(&term("-1")).is_err());
+    assert!(de::from_term::<u8>(&term("256")).is_err());
+    assert_eq!(2.0, de::from_term::<f64>(&term("2")).unwrap());
+    assert_eq!('a', de::from_term::<char>(&term("$a")).unwrap());
+    assert_eq!(None, de::from_term::<Option<u8>>(&term("nil")).unwrap());
+    assert_eq!(Some(1), de::from_term::<Option<u8>>(&term("1")).unwrap());
+
+    // Integers in self-describing positions
+    let cases = [
+        ("-1", Number::I64(-1)),
+        ("-9223372036854775808", Number::I64(i64::MIN)),
+        ("18446744073709551615", Number::U64(u64::MAX)),
+        ("-9223372036854775809", Number::I128(i64::MIN as i128 - 1)),
+        ("18446744073709551616", Number::I128(u64::MAX as i128 + 1)),
+        (
+            "340282366920938463463374607431768211455",
+            Number::U128(u128::MAX),
+        ),
+    ];
+    for (s, n) in &cases {
+        assert_eq!(*n, de::from_term::<Number>(&term(s)).unwrap(), "{}", s);
+    }
+    assert!(de::from_term::<Number>(&term("340282366920938463463374607431768211456")).is_err());
+
+    // Strings
+    for s in &["<<\"héllo\"/utf8>>", "\"héllo\"", "'héllo'"] {
+        assert_eq!("héllo", de::from_term::<String>(&term(s)).unwrap());
+    }
+    assert_eq!("abc", de::from_term::<&str>(&term("<<\"abc\">>")).unwrap());
+    assert!(de::from_term::<String>(&term("<<255>>")).is_err());
+    assert_eq!(
+        vec![1u8, 2],
+        de::from_term::<Vec<u8>>(&term("<<1,2>>")).unwrap()
+    );
+}
+
+#[test]
+fn deserialize_compound_test() {
+    assert_eq!(
+        (vec![1, 2], ("a".to_owned(), 'b')),
+        de::from_term(&term("{[1,2],{a,$b}}")).unwrap()
+    );
+    assert!(de::from_term::<(u8, u8)>(&term("{1,2,3}")).is_err());
+
+    let map: BTreeMap<String, u8> = de::from_term(&term("#{<<\"a\">> => 1,b => 2}")).unwrap();
+    assert_eq!(
+        vec![("a", 1), ("b", 2)],
+        map.iter()
+            .map(|(k, v)| (k.as_str(), *v))
+            .collect::<Vec<_>>()
+    );
+    let map: BTreeMap<String, u8> = de::from_term(&term("[{a,1},{b,2}]")).unwrap();
+    assert_eq!(2, map.len());
+
+    let account = Account {
+        id: 1,
+        name: "alice".to_owned(),
+        admin: true,
+        email: None,
+    };
+    for s in &[
+        "#{id => 1,name => <<\"alice\">>,admin => true,email => undefined}",
+        "#{<<\"id\">> => 1,<<\"name\">> => \"alice\",<<\"admin\">> => true}",
+        "[{id,1},{name,\"alice\"},admin]",
+        "{user,1,<<\"alice\">>,true,undefined}",
+    ] {
+        assert_eq!(account, de::from_term(&term(s)).unwrap());
+    }
+    assert!(de::from_term::<Account>(&term("{person,1,<<\"alice\">>,true,undefined}")).is_err());
+}
+
+#[test]
+fn deserialize_enum_test() {
+    assert_eq!(Command::Ping, de::from_term(&term("ping")).unwrap());
+    assert_eq!(
+        Command::Login(7),
+        de::from_term(&term("{login,7}")).unwrap()
+    );
+    assert_eq!(
+        Command::Move(1, -1),
+        de::from_term(&term("{move,1,-1}")).unwrap()
+    );
+    let rename = Command::Rename {
+        from: "a".to_owned(),
+        to: "b".to_owned(),
+    };
+    assert_eq!(
+        rename,
+        de::from_term(&term("{rename,#{from => \"a\",to => \"b\"}}")).unwrap()
+    );
+    assert_eq!(
+        rename,
+        de::from_term(&term("{rename,<<\"a\">>,<<\"b\">>}")).unwrap()
+    );
+
+    assert!(de::from_term::<Command>(&term("{login,1,2}")).is_err());
+    assert!(de::from_term::<Command>(&term("logout")).is_err());
+    let err = de::from_term::<Command>(&term("[ping]")).unwrap_err();
+    assert_eq!("[ping] is not an atom or a tagged tuple", err.to_string());
+}
+
+#[test]
+fn round_trip_test() {
+    let bytes = ser::to_vec(&Event::Rename {
+        from: "a".to_owned(),
+        to: "b".to_owned(),
+    })
+    .unwrap();
+    assert_eq!(
+        Command::Rename {
+            from: "a".to_owned(),
+            to: "b".to_owned()
+        },
+        de::from_slice(&bytes).unwrap()
+    );
+}
+
+#[test]
+fn elixir_struct_test() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "MyApp.User", deny_unknown_fields)]
+    struct ExUser {
+        id: u32,
+        name: String,
+    }
+
+    let user = ExUser {
+        id: 1,
+        name: "a".to_owned(),
+    };
+    let value = user
+        .serialize(Serializer::new().struct_repr(StructRepr::ElixirStruct))
+        .unwrap();
+    assert_eq!(
+        term("#{'__struct__' => 'Elixir.MyApp.User',id => 1,name => <<\"a\">>}"),
+        value
+    );
+    assert_eq!(user, de::from_term(&value).unwrap());
+    assert!(de::from_term::<ExUser>(&term("#{id => 1,name => <<\"a\">>,age => 2}")).is_err());
+}